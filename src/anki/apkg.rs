@@ -0,0 +1,499 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{Seek, Write};
+
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::drawing::piano::PianoError;
+use crate::questions::{Question, QuestionGenerator};
+
+// The id of our note type. Anki recognizes the note type on re-import by this id, so changing it
+// makes a second copy of the note type.
+const MODEL_ID: i64 = 1_690_000_000_000;
+
+// Anki's default deck and deck options, which every collection has.
+const DEFAULT_DECK_ID: i64 = 1;
+const DEFAULT_CONF_ID: i64 = 1;
+
+// The characters Anki uses to write note GUIDs, in base 91.
+const GUID_CHARACTERS: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!#$%&()*+,-./:;<=>?@[]^_`{|}~";
+
+// Anki separates the fields of a note with this character.
+const FIELD_SEPARATOR: &str = "\x1f";
+
+// The legacy (schema 11) collection format, which every version of Anki can import.
+const SCHEMA: &str = "
+    CREATE TABLE col (
+        id integer PRIMARY KEY, crt integer NOT NULL, mod integer NOT NULL,
+        scm integer NOT NULL, ver integer NOT NULL, dty integer NOT NULL, usn integer NOT NULL,
+        ls integer NOT NULL, conf text NOT NULL, models text NOT NULL, decks text NOT NULL,
+        dconf text NOT NULL, tags text NOT NULL
+    );
+    CREATE TABLE notes (
+        id integer PRIMARY KEY, guid text NOT NULL, mid integer NOT NULL, mod integer NOT NULL,
+        usn integer NOT NULL, tags text NOT NULL, flds text NOT NULL, sfld integer NOT NULL,
+        csum integer NOT NULL, flags integer NOT NULL, data text NOT NULL
+    );
+    CREATE TABLE cards (
+        id integer PRIMARY KEY, nid integer NOT NULL, did integer NOT NULL, ord integer NOT NULL,
+        mod integer NOT NULL, usn integer NOT NULL, type integer NOT NULL,
+        queue integer NOT NULL, due integer NOT NULL, ivl integer NOT NULL,
+        factor integer NOT NULL, reps integer NOT NULL, lapses integer NOT NULL,
+        left integer NOT NULL, odue integer NOT NULL, odid integer NOT NULL,
+        flags integer NOT NULL, data text NOT NULL
+    );
+    CREATE TABLE revlog (
+        id integer PRIMARY KEY, cid integer NOT NULL, usn integer NOT NULL, ivl integer NOT NULL,
+        lastIvl integer NOT NULL, factor integer NOT NULL, time integer NOT NULL,
+        type integer NOT NULL
+    );
+    CREATE TABLE graves (usn integer NOT NULL, oid integer NOT NULL, type integer NOT NULL);
+    CREATE INDEX ix_notes_usn ON notes (usn);
+    CREATE INDEX ix_cards_usn ON cards (usn);
+    CREATE INDEX ix_revlog_usn ON revlog (usn);
+    CREATE INDEX ix_cards_nid ON cards (nid);
+    CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+    CREATE INDEX ix_revlog_cid ON revlog (cid);
+    CREATE INDEX ix_notes_csum ON notes (csum);
+";
+
+#[derive(Debug)]
+pub enum ApkgError {
+    // Two questions would get the same GUID, so one would overwrite the other in Anki.
+    DuplicateQuestion { prompt: String },
+    Piano(PianoError),
+    Sqlite(rusqlite::Error),
+    Zip(zip::result::ZipError),
+    Io(std::io::Error),
+}
+
+impl From<PianoError> for ApkgError {
+    fn from(err: PianoError) -> Self {
+        ApkgError::Piano(err)
+    }
+}
+
+impl From<rusqlite::Error> for ApkgError {
+    fn from(err: rusqlite::Error) -> Self {
+        ApkgError::Sqlite(err)
+    }
+}
+
+impl From<zip::result::ZipError> for ApkgError {
+    fn from(err: zip::result::ZipError) -> Self {
+        ApkgError::Zip(err)
+    }
+}
+
+impl From<std::io::Error> for ApkgError {
+    fn from(err: std::io::Error) -> Self {
+        ApkgError::Io(err)
+    }
+}
+
+// Writes every question from the generators to an Anki package, with one note (and card) per
+// question, and the piano diagrams rendered into the package's media.
+//
+// Each question goes in a subdeck of `root_deck` named by its first two tags, like
+// "Edification::scales::ascending". Notes get GUIDs from their category and prompt, so importing
+// a newer export updates the notes from an older one instead of adding duplicates. Anki only
+// updates notes that were modified more recently, so `modified` should be the current time, in
+// seconds since the Unix epoch.
+pub fn write_apkg<W: Write + Seek>(
+    writer: W,
+    root_deck: &str,
+    generators: &[Box<dyn QuestionGenerator>],
+    modified: i64,
+) -> Result<W, ApkgError> {
+    let questions: Vec<Question> = generators
+        .iter()
+        .flat_map(|generator| generator.questions())
+        .collect();
+
+    let connection = Connection::open_in_memory()?;
+    connection.execute_batch(SCHEMA)?;
+
+    let mut decks = BTreeSet::new();
+    let mut guids = HashSet::new();
+    let mut media = BTreeMap::new();
+
+    for (position, question) in questions.iter().enumerate() {
+        let guid = guid(question);
+        if !guids.insert(guid.clone()) {
+            return Err(ApkgError::DuplicateQuestion {
+                prompt: question.prompt.clone(),
+            });
+        }
+
+        // Every prefix of the deck name is a deck too, so that Anki shows the hierarchy.
+        let deck = deck_name(root_deck, question);
+        let mut parent = String::new();
+        for part in deck.split("::") {
+            if !parent.is_empty() {
+                parent.push_str("::");
+            }
+            parent.push_str(part);
+            decks.insert(parent.clone());
+        }
+
+        for diagram in question.media.iter() {
+            if !media.contains_key(&diagram.filename) {
+                media.insert(diagram.filename.clone(), diagram.render()?.to_svg());
+            }
+        }
+
+        let images: String = question
+            .media
+            .iter()
+            .map(|diagram| format!("<img src=\"{}\">", diagram.filename.replace('#', "%23")))
+            .collect();
+        let fields = [
+            escape_html(&question.prompt),
+            escape_html(&question.answer),
+            images,
+        ];
+
+        let id = id_for(&guid);
+        connection.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            params![
+                id,
+                guid,
+                MODEL_ID,
+                modified,
+                format!(" {} ", question.tags.join(" ")),
+                fields.join(FIELD_SEPARATOR),
+                fields[0],
+                checksum(&fields[0]),
+            ],
+        )?;
+
+        // A new card, due in the order the questions were generated.
+        connection.execute(
+            "INSERT INTO cards VALUES (?1, ?1, ?2, 0, ?3, -1, 0, 0, ?4, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![id, id_for(&deck), modified, position as i64 + 1],
+        )?;
+    }
+
+    connection.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?2, 11, 0, 0, 0, ?3, ?4, ?5, ?6, '{}')",
+        params![
+            modified,
+            modified * 1000,
+            collection_conf().to_string(),
+            models(modified).to_string(),
+            decks_json(&decks, modified).to_string(),
+            deck_conf().to_string(),
+        ],
+    )?;
+
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("collection.anki2", options)?;
+    zip.write_all(&connection.serialize("main")?)?;
+
+    // Media files are stored under their index, and the "media" file maps them back to names.
+    let mut media_map = serde_json::Map::new();
+    for (index, (filename, svg)) in media.iter().enumerate() {
+        zip.start_file(index.to_string(), options)?;
+        zip.write_all(svg.as_bytes())?;
+        media_map.insert(index.to_string(), Value::from(filename.as_str()));
+    }
+    zip.start_file("media", options)?;
+    zip.write_all(Value::Object(media_map).to_string().as_bytes())?;
+
+    Ok(zip.finish()?)
+}
+
+// The deck a question goes in: the root deck, then its first two tags, like
+// "Edification::tensions::dominant-seventh".
+pub fn deck_name(root_deck: &str, question: &Question) -> String {
+    std::iter::once(root_deck)
+        .chain(question.tags.iter().take(2).map(|tag| tag.as_str()))
+        .collect::<Vec<&str>>()
+        .join("::")
+}
+
+// A GUID that only depends on the question's category and prompt, so that fixing an answer or
+// adding media updates the existing note. Written in base 91 like the GUIDs Anki makes.
+pub fn guid(question: &Question) -> String {
+    let category = question.tags.first().map(|tag| tag.as_str()).unwrap_or("");
+    let mut value = hash(&format!("{category}{FIELD_SEPARATOR}{}", question.prompt));
+
+    let mut guid = vec![];
+    loop {
+        guid.push(GUID_CHARACTERS[(value % 91) as usize]);
+        value /= 91;
+        if value == 0 {
+            break;
+        }
+    }
+    guid.reverse();
+    String::from_utf8(guid).unwrap()
+}
+
+// The first 8 bytes of the SHA-1 of the text.
+fn hash(text: &str) -> u64 {
+    let digest = Sha1::digest(text.as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+// A stable id for a note, card or deck. Anki's own ids are millisecond timestamps, so we keep ours
+// to a similar size.
+fn id_for(text: &str) -> i64 {
+    (hash(text) >> 24) as i64
+}
+
+// Anki detects duplicate notes with the first 8 hex digits of the SHA-1 of the sort field.
+fn checksum(sort_field: &str) -> i64 {
+    (hash(sort_field) >> 32) as i64
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn models(modified: i64) -> Value {
+    let field = |name: &str, ord: usize| {
+        json!({
+            "name": name, "ord": ord, "sticky": false, "rtl": false, "font": "Arial",
+            "size": 20, "media": []
+        })
+    };
+
+    json!({
+        MODEL_ID.to_string(): {
+            "id": MODEL_ID,
+            "name": "Edification",
+            "type": 0,
+            "mod": modified,
+            "usn": -1,
+            "sortf": 0,
+            "did": DEFAULT_DECK_ID,
+            "flds": [field("Prompt", 0), field("Answer", 1), field("Media", 2)],
+            "tmpls": [{
+                "name": "Card 1",
+                "ord": 0,
+                "qfmt": "{{Prompt}}",
+                "afmt": "{{FrontSide}}<hr id=answer>{{Answer}}<br>{{Media}}",
+                "did": null,
+                "bqfmt": "",
+                "bafmt": "",
+            }],
+            "css": ".card { font-family: arial; font-size: 20px; text-align: center; }",
+            "latexPre": "",
+            "latexPost": "",
+            "latexsvg": false,
+            "req": [[0, "all", [0]]],
+            "tags": [],
+            "vers": [],
+        }
+    })
+}
+
+fn decks_json(names: &BTreeSet<String>, modified: i64) -> Value {
+    let deck = |id: i64, name: &str| {
+        json!({
+            "id": id, "name": name, "mod": modified, "usn": -1, "desc": "", "dyn": 0,
+            "conf": DEFAULT_CONF_ID, "collapsed": false, "browserCollapsed": false,
+            "extendNew": 0, "extendRev": 0, "newToday": [0, 0], "revToday": [0, 0],
+            "lrnToday": [0, 0], "timeToday": [0, 0],
+        })
+    };
+
+    let mut decks = serde_json::Map::new();
+    decks.insert(
+        DEFAULT_DECK_ID.to_string(),
+        deck(DEFAULT_DECK_ID, "Default"),
+    );
+    for name in names {
+        decks.insert(id_for(name).to_string(), deck(id_for(name), name));
+    }
+    Value::Object(decks)
+}
+
+fn collection_conf() -> Value {
+    json!({
+        "activeDecks": [DEFAULT_DECK_ID], "curDeck": DEFAULT_DECK_ID,
+        "curModel": MODEL_ID.to_string(), "nextPos": 1, "sortType": "noteFld",
+        "sortBackwards": false, "addToCur": true, "collapseTime": 1200, "dueCounts": true,
+        "estTimes": true, "newBury": true, "newSpread": 0, "timeLim": 0,
+    })
+}
+
+fn deck_conf() -> Value {
+    json!({
+        DEFAULT_CONF_ID.to_string(): {
+            "id": DEFAULT_CONF_ID, "name": "Default", "mod": 0, "usn": 0, "maxTaken": 60,
+            "autoplay": true, "replayq": true, "timer": 0, "dyn": false,
+            "new": {
+                "bury": true, "delays": [1, 10], "initialFactor": 2500, "ints": [1, 4, 7],
+                "order": 1, "perDay": 20, "separate": true,
+            },
+            "rev": {
+                "bury": true, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1, "maxIvl": 36500,
+                "minSpace": 1, "perDay": 100,
+            },
+            "lapse": {
+                "delays": [10], "leechAction": 0, "leechFails": 8, "minInt": 1, "mult": 0,
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use zip::ZipArchive;
+
+    use crate::questions::all_generators;
+
+    use super::*;
+
+    fn export(modified: i64) -> Vec<u8> {
+        write_apkg(
+            Cursor::new(vec![]),
+            "Edification",
+            &all_generators(),
+            modified,
+        )
+        .unwrap()
+        .into_inner()
+    }
+
+    fn read(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Vec<u8> {
+        let mut contents = vec![];
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    fn open(apkg: Vec<u8>) -> (Connection, Value) {
+        let mut archive = ZipArchive::new(Cursor::new(apkg)).unwrap();
+        let collection = read(&mut archive, "collection.anki2");
+        let media: Value = serde_json::from_slice(&read(&mut archive, "media")).unwrap();
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        connection
+            .deserialize_read_exact("main", Cursor::new(&collection), collection.len(), true)
+            .unwrap();
+        (connection, media)
+    }
+
+    fn guids(connection: &Connection) -> Vec<String> {
+        connection
+            .prepare("SELECT guid FROM notes ORDER BY guid")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|guid| guid.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_notes_and_cards() {
+        let (connection, _) = open(export(1_700_000_000));
+        let num_questions: i64 = all_generators()
+            .iter()
+            .map(|generator| generator.questions().len() as i64)
+            .sum();
+
+        let count = |table: &str| -> i64 {
+            connection
+                .query_row(&format!("SELECT count(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        assert_eq!(count("notes"), num_questions);
+        assert_eq!(count("cards"), num_questions);
+
+        let (fields, tags): (String, String) = connection
+            .query_row(
+                "SELECT flds, tags FROM notes WHERE sfld = 'What is the b13 of C7?'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            fields,
+            "What is the b13 of C7?\x1fAb\x1f<img src=\"C4-Tb13.svg\">"
+        );
+        assert_eq!(tags, " tensions dominant-seventh Tb13 ");
+    }
+
+    #[test]
+    fn test_guids_are_stable() {
+        let (first, _) = open(export(1_700_000_000));
+        let (second, _) = open(export(1_800_000_000));
+        assert_eq!(guids(&first), guids(&second));
+
+        let question = &all_generators()[0].questions()[0];
+        assert_eq!(guid(question), guid(question));
+        assert!(guid(question).bytes().all(|c| GUID_CHARACTERS.contains(&c)));
+    }
+
+    #[test]
+    fn test_decks() {
+        let (connection, _) = open(export(1_700_000_000));
+        let decks: String = connection
+            .query_row("SELECT decks FROM col", [], |row| row.get(0))
+            .unwrap();
+        let decks: Value = serde_json::from_str(&decks).unwrap();
+        let names: Vec<&str> = decks
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|deck| deck["name"].as_str().unwrap())
+            .collect();
+
+        for name in [
+            "Default",
+            "Edification",
+            "Edification::scales",
+            "Edification::scales::ascending",
+            "Edification::tensions::dominant-seventh",
+            "Edification::harmony::harmonic-minor",
+        ] {
+            assert!(names.contains(&name), "missing deck {name}");
+        }
+
+        // Every card is in a deck that exists.
+        let deck_ids: Vec<i64> = connection
+            .prepare("SELECT DISTINCT did FROM cards")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|id| id.unwrap())
+            .collect();
+        for id in deck_ids {
+            assert!(decks.get(id.to_string()).is_some());
+        }
+    }
+
+    #[test]
+    fn test_media() {
+        let apkg = export(1_700_000_000);
+        let (_, media) = open(apkg.clone());
+        let media = media.as_object().unwrap();
+
+        let (index, _) = media
+            .iter()
+            .find(|(_, filename)| filename.as_str() == Some("C4-Tb13.svg"))
+            .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(apkg)).unwrap();
+        let svg = String::from_utf8(read(&mut archive, index)).unwrap();
+        assert!(svg.starts_with("<svg"));
+    }
+}
@@ -44,7 +44,7 @@ pub struct Piano {
     padding_y: f32,
 }
 
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum HighlightColor {
     Red,
     Green,
@@ -56,6 +56,12 @@ pub enum PianoError {
     InvalidNoteString,
 }
 
+impl Default for Piano {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Piano {
     pub fn new() -> Self {
         let num_octaves: i8 = 2;
@@ -72,8 +78,8 @@ impl Piano {
 
     // Highlights a single note on the piano instance.
     pub fn highlight_note(&mut self, note: &str, color: HighlightColor) -> Result<(), PianoError> {
-        let note = Note::from_str(note).or_else(|err| match err {
-            NoteParseError::InvalidFormat => Err(PianoError::InvalidNoteString),
+        let note = Note::from_str(note).map_err(|err| match err {
+            NoteParseError::InvalidFormat => PianoError::InvalidNoteString,
         })?;
         self.highlighted.push(HighlightedNote { note, color });
 
@@ -87,9 +93,14 @@ impl Piano {
         svg::save(filepath, &document).unwrap();
     }
 
+    // Renders the piano as the contents of an SVG file.
+    pub fn to_svg(&self) -> String {
+        self.render_piano().to_string()
+    }
+
     fn render_piano(&self) -> Document {
-        let true_height = self.width + (2.0 * self.padding_y) as f32;
-        let true_width = self.width + (2.0 * self.padding_x) as f32;
+        let true_height = self.width + (2.0 * self.padding_y);
+        let true_width = self.width + (2.0 * self.padding_x);
 
         let mut document = Document::new().set("viewBox", (0, 0, true_width, true_height));
 
@@ -161,11 +172,10 @@ impl Piano {
             KeyColor::Black => self.height * 0.689,
         };
 
-        // TODO(neil): We should have a notion of enharmonic equality somewhere
         let highlighted_note = self
             .highlighted
             .iter()
-            .find(|x| x.note.inter_octave_semitone_value() == note.inter_octave_semitone_value());
+            .find(|x| x.note.is_enharmonic_to(note));
 
         let visual_spec = match note.key_color() {
             KeyColor::White => highlighted_note.map_or("white".to_string(), |v| v.to_hex()),
@@ -1,36 +1,38 @@
 use std::{
-    fs::{self, create_dir, create_dir_all},
-    str::FromStr,
+    collections::HashSet,
+    fs::{create_dir_all, File},
+    time::SystemTime,
 };
 
-use drawing::piano::HighlightColor;
-use theory::{interval::TFLAT13, note::Note, scale::CHROMATIC};
-
+pub mod anki;
 pub mod drawing;
+pub mod questions;
 pub mod theory;
 
 fn main() {
-    let chromatic = Note::from_str("C").unwrap().ascending_scale(&CHROMATIC);
-
     // Ensure that images/ exists
     create_dir_all("images").unwrap();
 
-    for note in chromatic.iter() {
-        // For all of these, let's generate a piano with the Tb13
-        let path = format!("images/{note}-Tb13.svg");
-
-        let mut piano = drawing::piano::Piano::new();
-
-        piano
-            .highlight_note(&note.to_string(), HighlightColor::Red)
-            .unwrap();
-        piano
-            .highlight_note(
-                &note.apply_interval(&TFLAT13).to_string(),
-                HighlightColor::Green,
-            )
-            .unwrap();
-
-        piano.save(&path);
+    // Questions can share media, so each file only needs to be drawn once.
+    let mut saved = HashSet::new();
+
+    let generators = questions::all_generators();
+    for generator in generators.iter() {
+        for question in generator.questions() {
+            for media in question.media.iter() {
+                if saved.insert(media.filename.clone()) {
+                    let path = format!("images/{}", media.filename);
+                    media.render().unwrap().save(&path);
+                }
+            }
+        }
     }
+
+    // Importing this into Anki again later updates the cards instead of duplicating them.
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let apkg = File::create("edification.apkg").unwrap();
+    anki::apkg::write_apkg(apkg, "Edification", &generators, now).unwrap();
 }
@@ -0,0 +1,136 @@
+use lazy_static::lazy_static;
+
+use crate::questions::{roots, spell, tag, Question, QuestionGenerator};
+use crate::theory::chord::ALL_CHORD_QUALITIES;
+use crate::theory::harmony::{harmonize, ChordSize};
+use crate::theory::scale::{Scale, HARMONIC_MINOR, IONIAN, MELODIC_MINOR};
+
+// "Spell Cm7." for every chord quality on every root.
+pub struct ChordSpelling;
+
+impl QuestionGenerator for ChordSpelling {
+    fn name(&self) -> &'static str {
+        "chords"
+    }
+
+    fn questions(&self) -> Vec<Question> {
+        let mut questions = vec![];
+        for quality in ALL_CHORD_QUALITIES.iter() {
+            for root in roots() {
+                let notes = match root.try_chord(quality) {
+                    Ok(notes) => notes,
+                    Err(_) => continue,
+                };
+
+                questions.push(Question {
+                    prompt: format!("Spell {}{}.", root.name(), quality.symbol),
+                    answer: spell(&notes),
+                    tags: vec![self.name().to_string(), tag(quality.name)],
+                    media: vec![],
+                });
+            }
+        }
+
+        questions
+    }
+}
+
+lazy_static! {
+    // The scales whose modes' chords we ask about in ModeDegreeQuality.
+    static ref PARENT_SCALES: Vec<&'static Scale> = vec![&IONIAN, &HARMONIC_MINOR, &MELODIC_MINOR];
+}
+
+// "What is the quality of the seventh chord on degree 3 of C harmonic minor?" for every degree
+// of the major, harmonic minor and melodic minor scales, on every root. The answer names the
+// quality, then the chord symbol and roman numeral.
+pub struct ModeDegreeQuality;
+
+impl QuestionGenerator for ModeDegreeQuality {
+    fn name(&self) -> &'static str {
+        "harmony"
+    }
+
+    fn questions(&self) -> Vec<Question> {
+        let mut questions = vec![];
+        for scale in PARENT_SCALES.iter() {
+            for root in roots() {
+                let chords = match harmonize(&root, scale, ChordSize::Seventh) {
+                    Ok(chords) => chords,
+                    Err(_) => continue,
+                };
+
+                for chord in chords {
+                    let (quality, symbol, numeral) =
+                        match (chord.quality, chord.chord_symbol(), chord.roman_numeral()) {
+                            (Some(quality), Some(symbol), Some(numeral)) => {
+                                (quality, symbol, numeral)
+                            }
+                            _ => continue,
+                        };
+
+                    questions.push(Question {
+                        prompt: format!(
+                            "What is the quality of the seventh chord on degree {} of {} {}?",
+                            chord.degree,
+                            root.name(),
+                            scale.name
+                        ),
+                        answer: format!("{} ({symbol}, {numeral})", quality.name),
+                        tags: vec![
+                            self.name().to_string(),
+                            tag(scale.name),
+                            format!("degree-{}", chord.degree),
+                        ],
+                        media: vec![],
+                    });
+                }
+            }
+        }
+
+        questions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(generator: &dyn QuestionGenerator, prompt: &str) -> String {
+        generator
+            .questions()
+            .into_iter()
+            .find(|question| question.prompt == prompt)
+            .unwrap_or_else(|| panic!("no question {prompt}"))
+            .answer
+    }
+
+    #[test]
+    fn test_chord_spelling() {
+        assert_eq!(answer(&ChordSpelling, "Spell Dbm7."), "Db Fb Ab Cb");
+        assert_eq!(answer(&ChordSpelling, "Spell Bm7b5."), "B D F A");
+        assert_eq!(answer(&ChordSpelling, "Spell Ab7."), "Ab C Eb Gb");
+        assert_eq!(
+            ChordSpelling.questions().len(),
+            12 * ALL_CHORD_QUALITIES.len()
+        );
+    }
+
+    #[test]
+    fn test_mode_degree_quality() {
+        assert_eq!(
+            answer(
+                &ModeDegreeQuality,
+                "What is the quality of the seventh chord on degree 5 of Eb ionian?"
+            ),
+            "dominant seventh (Bb7, V7)"
+        );
+        assert_eq!(
+            answer(
+                &ModeDegreeQuality,
+                "What is the quality of the seventh chord on degree 7 of A harmonic minor?"
+            ),
+            "diminished seventh (G#dim7, vii°7)"
+        );
+        assert_eq!(ModeDegreeQuality.questions().len(), 12 * 3 * 7);
+    }
+}
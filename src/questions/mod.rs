@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use crate::drawing::piano::{HighlightColor, Piano, PianoError};
+use crate::theory::{note::Note, scale::CHROMATIC};
+
+pub mod chords;
+pub mod scales;
+pub mod tensions;
+
+// A single flashcard: something to ask, and what a correct answer looks like.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Question {
+    pub prompt: String,
+    pub answer: String,
+
+    // What the question is about, most general first, like ["scales", "ascending", "dorian"].
+    // Tags never contain spaces.
+    pub tags: Vec<String>,
+
+    // Pictures that go along with the answer.
+    pub media: Vec<Media>,
+}
+
+// A piano diagram with some of its keys highlighted. Questions only describe their media; it's
+// up to the caller to render and save them.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Media {
+    // A file name that's unique to what the diagram shows, so questions that show the same thing
+    // can share the file.
+    pub filename: String,
+    pub highlights: Vec<(Note, HighlightColor)>,
+}
+
+impl Media {
+    pub fn render(&self) -> Result<Piano, PianoError> {
+        let mut piano = Piano::new();
+        for (note, color) in self.highlights.iter() {
+            piano.highlight_note(&note.to_string(), color.clone())?;
+        }
+        Ok(piano)
+    }
+}
+
+// A category of questions. Adding a category means implementing this and listing it in
+// all_generators.
+pub trait QuestionGenerator {
+    // A short name for the category, which is also the first tag of all of its questions.
+    fn name(&self) -> &'static str;
+
+    // Every question in the category, over all roots.
+    fn questions(&self) -> Vec<Question>;
+}
+
+// One generator for each category of question in the README.
+pub fn all_generators() -> Vec<Box<dyn QuestionGenerator>> {
+    vec![
+        Box::new(scales::ScaleSpelling {
+            direction: scales::ScaleDirection::Ascending,
+        }),
+        Box::new(scales::ScaleSpelling {
+            direction: scales::ScaleDirection::Descending,
+        }),
+        Box::new(tensions::TensionNaming),
+        Box::new(chords::ChordSpelling),
+        Box::new(chords::ModeDegreeQuality),
+    ]
+}
+
+// The 12 roots that questions are asked about, spelled like the chromatic scale from C: C, Db,
+// D, Eb, and so on.
+pub fn roots() -> Vec<Note> {
+    Note::from_str("C").unwrap().ascending_scale(&CHROMATIC)
+}
+
+// Turns a name like "melodic minor" into a tag like "melodic-minor".
+pub fn tag(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<&str>>().join("-")
+}
+
+// Note names separated by spaces, without octaves.
+fn spell(notes: &[Note]) -> String {
+    notes
+        .iter()
+        .map(|note| note.name())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::theory::chord::ChordSymbol;
+
+    use super::*;
+
+    #[test]
+    fn test_roots() {
+        assert_eq!(spell(&roots()), "C Db D Eb E F Gb G Ab A Bb B");
+    }
+
+    #[test]
+    fn test_slash_chord_media() {
+        // Slash chords are voiced with the bass in the diagram's range, so its key is highlighted.
+        for symbol in ["C/E", "Cm7/Bb", "Eb/G", "G7(b9)/F"] {
+            let notes = ChordSymbol::from_str(symbol).unwrap().notes();
+            let media = Media {
+                filename: "slash.svg".to_string(),
+                highlights: notes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, note)| match i {
+                        0 => (*note, HighlightColor::Red),
+                        _ => (*note, HighlightColor::Green),
+                    })
+                    .collect(),
+            };
+
+            let svg = media.render().unwrap().to_svg();
+            assert_eq!(svg.matches("#e74c3c").count(), 1, "{symbol}");
+            assert_eq!(svg.matches("#2ecc71").count(), notes.len() - 1, "{symbol}");
+        }
+    }
+
+    #[test]
+    fn test_tags() {
+        assert_eq!(tag("melodic minor"), "melodic-minor");
+        assert_eq!(tag("dorian"), "dorian");
+
+        for generator in all_generators() {
+            for question in generator.questions() {
+                assert_eq!(question.tags[0], generator.name());
+                assert!(question.tags.iter().all(|tag| !tag.contains(' ')));
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::questions::{roots, spell, tag, Question, QuestionGenerator};
+use crate::theory::interval::OCT1;
+use crate::theory::scale::{ALL_SCALES, DESCENDING_VARIANTS};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScaleDirection {
+    Ascending,
+    Descending,
+}
+
+// "Spell C dorian ascending." for every scale in the catalog on every root. Answers run from the
+// root to the octave in either direction. Descending answers use the scale's descending form if
+// it has one, so they also cover scales like classical melodic minor that only differ on the way
+// down. Roots that would need more than a double accidental are skipped.
+pub struct ScaleSpelling {
+    pub direction: ScaleDirection,
+}
+
+impl QuestionGenerator for ScaleSpelling {
+    fn name(&self) -> &'static str {
+        "scales"
+    }
+
+    fn questions(&self) -> Vec<Question> {
+        let direction = match self.direction {
+            ScaleDirection::Ascending => "ascending",
+            ScaleDirection::Descending => "descending",
+        };
+
+        // Scales that only differ on the way down would repeat an ascending question.
+        let variants = match self.direction {
+            ScaleDirection::Ascending => &[][..],
+            ScaleDirection::Descending => &DESCENDING_VARIANTS[..],
+        };
+
+        let mut questions = vec![];
+        for scale in ALL_SCALES.iter().chain(variants.iter()) {
+            for root in roots() {
+                let notes = match self.direction {
+                    ScaleDirection::Ascending => {
+                        root.try_ascending_scale(scale).and_then(|mut notes| {
+                            notes.push(root.try_apply_interval(&OCT1)?);
+                            Ok(notes)
+                        })
+                    }
+                    ScaleDirection::Descending => root.try_descending_scale(scale),
+                };
+                let notes = match notes {
+                    Ok(notes) => notes,
+                    Err(_) => continue,
+                };
+
+                questions.push(Question {
+                    prompt: format!("Spell {} {} {direction}.", root.name(), scale.name),
+                    answer: spell(&notes),
+                    tags: vec![
+                        self.name().to_string(),
+                        direction.to_string(),
+                        tag(scale.name),
+                    ],
+                    media: vec![],
+                });
+            }
+        }
+
+        questions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(direction: ScaleDirection, prompt: &str) -> String {
+        ScaleSpelling { direction }
+            .questions()
+            .into_iter()
+            .find(|question| question.prompt == prompt)
+            .unwrap()
+            .answer
+    }
+
+    #[test]
+    fn test_scale_spelling() {
+        assert_eq!(
+            answer(ScaleDirection::Ascending, "Spell Eb dorian ascending."),
+            "Eb F Gb Ab Bb C Db Eb"
+        );
+        assert_eq!(
+            answer(ScaleDirection::Descending, "Spell C dorian descending."),
+            "C Bb A G F Eb D C"
+        );
+        assert_eq!(
+            answer(
+                ScaleDirection::Descending,
+                "Spell A classical melodic minor descending."
+            ),
+            "A G F E D C B A"
+        );
+
+        let questions = ScaleSpelling {
+            direction: ScaleDirection::Ascending,
+        }
+        .questions();
+        assert_eq!(questions[0].tags, ["scales", "ascending", "chromatic"]);
+        assert!(questions.len() > 12 * 30);
+        assert!(!questions
+            .iter()
+            .any(|question| question.prompt.contains("classical")));
+    }
+}
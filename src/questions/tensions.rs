@@ -0,0 +1,108 @@
+use crate::drawing::piano::HighlightColor;
+use crate::questions::{roots, tag, Media, Question, QuestionGenerator};
+use crate::theory::chord::{ChordQuality, ALL_CHORD_QUALITIES};
+use crate::theory::interval::*;
+
+// "What is the b13 of C7?" for every available tension of every chord quality, on every root.
+// The answer comes with a piano showing the root in red and the tension in green.
+//
+// Dominant chords are asked about every dominant tension, not just the ones in their default
+// chord-scale, since altered tensions are what get played on them.
+pub struct TensionNaming;
+
+impl QuestionGenerator for TensionNaming {
+    fn name(&self) -> &'static str {
+        "tensions"
+    }
+
+    fn questions(&self) -> Vec<Question> {
+        let mut questions = vec![];
+        for quality in ALL_CHORD_QUALITIES.iter() {
+            for tension in tensions(quality) {
+                let tension_name = tension
+                    .tension_name()
+                    .unwrap_or_else(|| tension.to_string());
+
+                for root in roots() {
+                    let note = match root.try_apply_interval(&tension) {
+                        Ok(note) => note,
+                        Err(_) => continue,
+                    };
+
+                    questions.push(Question {
+                        prompt: format!(
+                            "What is the {tension_name} of {}{}?",
+                            root.name(),
+                            quality.symbol
+                        ),
+                        answer: note.name(),
+                        tags: vec![
+                            self.name().to_string(),
+                            tag(quality.name),
+                            format!("T{tension_name}"),
+                        ],
+                        media: vec![Media {
+                            filename: format!("{root}-T{tension_name}.svg"),
+                            highlights: vec![
+                                (root, HighlightColor::Red),
+                                (note, HighlightColor::Green),
+                            ],
+                        }],
+                    });
+                }
+            }
+        }
+
+        questions
+    }
+}
+
+fn tensions(quality: &ChordQuality) -> Vec<Interval> {
+    if !quality.is_dominant() || !quality.intervals.contains(&MAJ3) {
+        return quality.tensions().available;
+    }
+
+    // Leave out tensions that are already chord tones, like the b13 of a 7#5.
+    [TFLAT9, T9, TSHARP9, TSHARP11, TFLAT13, T13]
+        .into_iter()
+        .filter(|tension| {
+            !quality
+                .intervals
+                .iter()
+                .any(|interval| interval.semitones % 12 == tension.semitones % 12)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tension_naming() {
+        let questions = TensionNaming.questions();
+        let find = |prompt: &str| {
+            questions
+                .iter()
+                .find(|question| question.prompt == prompt)
+                .unwrap_or_else(|| panic!("no question {prompt}"))
+        };
+
+        let c7_b13 = find("What is the b13 of C7?");
+        assert_eq!(c7_b13.answer, "Ab");
+        assert_eq!(c7_b13.tags, ["tensions", "dominant-seventh", "Tb13"]);
+        assert_eq!(c7_b13.media[0].filename, "C4-Tb13.svg");
+
+        assert_eq!(find("What is the #9 of Ab7?").answer, "B");
+        assert_eq!(find("What is the 13 of Dbmaj7?").answer, "Bb");
+        assert_eq!(find("What is the 11 of Gbm7?").answer, "Cb");
+        assert!(!questions
+            .iter()
+            .any(|question| question.prompt == "What is the b13 of C7#5?"));
+
+        // The 11 is an avoid note over a major seventh chord.
+        assert!(!questions
+            .iter()
+            .any(|question| question.prompt == "What is the 11 of Cmaj7?"));
+    }
+}
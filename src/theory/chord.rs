@@ -0,0 +1,903 @@
+use std::str::FromStr;
+
+use crate::theory::interval::*;
+use crate::theory::note::{ApplyIntervalError, Note, NOTE_NAME_PATTERN};
+use crate::theory::scale::*;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ChordQuality {
+    // A human-readable name, like "dominant seventh".
+    pub name: &'static str,
+
+    // The canonical suffix used when writing a chord symbol, like the "m7" in "Dm7". Major triads
+    // have an empty symbol.
+    pub symbol: &'static str,
+
+    // The chord tones, starting at the root and stacked in thirds. Chords that replace a third or
+    // seventh (sus chords, 6 chords) keep the replacement in that slot, so index 1 is always the
+    // "third" and index 3, if present, is always the "seventh".
+    pub intervals: Vec<Interval>,
+
+    // The scale we assume is being played over this chord when nothing else is specified. This is
+    // what determines the chord's available tensions and avoid notes.
+    pub chord_scale: &'static Scale,
+}
+
+lazy_static! {
+    // Triads
+
+    pub static ref MAJOR_TRIAD: ChordQuality = ChordQuality {
+        name: "major",
+        symbol: "",
+        intervals: vec![PERF1, MAJ3, PERF5],
+        chord_scale: &IONIAN,
+    };
+
+    pub static ref MINOR_TRIAD: ChordQuality = ChordQuality {
+        name: "minor",
+        symbol: "m",
+        intervals: vec![PERF1, MIN3, PERF5],
+        chord_scale: &DORIAN,
+    };
+
+    pub static ref DIMINISHED_TRIAD: ChordQuality = ChordQuality {
+        name: "diminished",
+        symbol: "dim",
+        intervals: vec![PERF1, MIN3, TRITONE5],
+        chord_scale: &LOCRIAN,
+    };
+
+    pub static ref AUGMENTED_TRIAD: ChordQuality = ChordQuality {
+        name: "augmented",
+        symbol: "aug",
+        intervals: vec![PERF1, MAJ3, AUG5],
+        chord_scale: &WHOLE_TONE,
+    };
+
+    pub static ref SUS2: ChordQuality = ChordQuality {
+        name: "suspended second",
+        symbol: "sus2",
+        intervals: vec![PERF1, MAJ2, PERF5],
+        chord_scale: &MIXO,
+    };
+
+    pub static ref SUS4: ChordQuality = ChordQuality {
+        name: "suspended fourth",
+        symbol: "sus4",
+        intervals: vec![PERF1, PERF4, PERF5],
+        chord_scale: &MIXO,
+    };
+
+    // Sixth chords
+
+    pub static ref MAJOR_SIXTH: ChordQuality = ChordQuality {
+        name: "major sixth",
+        symbol: "6",
+        intervals: vec![PERF1, MAJ3, PERF5, MAJ6],
+        chord_scale: &IONIAN,
+    };
+
+    pub static ref MINOR_SIXTH: ChordQuality = ChordQuality {
+        name: "minor sixth",
+        symbol: "m6",
+        intervals: vec![PERF1, MIN3, PERF5, MAJ6],
+        chord_scale: &DORIAN,
+    };
+
+    // Seventh chords
+
+    pub static ref MAJOR_SEVENTH: ChordQuality = ChordQuality {
+        name: "major seventh",
+        symbol: "maj7",
+        intervals: vec![PERF1, MAJ3, PERF5, MAJ7],
+        chord_scale: &IONIAN,
+    };
+
+    pub static ref DOMINANT_SEVENTH: ChordQuality = ChordQuality {
+        name: "dominant seventh",
+        symbol: "7",
+        intervals: vec![PERF1, MAJ3, PERF5, MIN7],
+        chord_scale: &MIXO,
+    };
+
+    pub static ref MINOR_SEVENTH: ChordQuality = ChordQuality {
+        name: "minor seventh",
+        symbol: "m7",
+        intervals: vec![PERF1, MIN3, PERF5, MIN7],
+        chord_scale: &DORIAN,
+    };
+
+    pub static ref HALF_DIMINISHED_SEVENTH: ChordQuality = ChordQuality {
+        name: "half-diminished seventh",
+        symbol: "m7b5",
+        intervals: vec![PERF1, MIN3, TRITONE5, MIN7],
+        chord_scale: &LOCRIAN,
+    };
+
+    pub static ref DIMINISHED_SEVENTH: ChordQuality = ChordQuality {
+        name: "diminished seventh",
+        symbol: "dim7",
+        intervals: vec![PERF1, MIN3, TRITONE5, DIM7],
+        chord_scale: &DIMINISHED_WHOLE_HALF,
+    };
+
+    pub static ref MINOR_MAJOR_SEVENTH: ChordQuality = ChordQuality {
+        name: "minor-major seventh",
+        symbol: "mMaj7",
+        intervals: vec![PERF1, MIN3, PERF5, MAJ7],
+        chord_scale: &MELODIC_MINOR,
+    };
+
+    pub static ref DOMINANT_SEVENTH_SUS4: ChordQuality = ChordQuality {
+        name: "dominant seventh suspended fourth",
+        symbol: "7sus4",
+        intervals: vec![PERF1, PERF4, PERF5, MIN7],
+        chord_scale: &MIXO,
+    };
+
+    pub static ref AUGMENTED_SEVENTH: ChordQuality = ChordQuality {
+        name: "augmented seventh",
+        symbol: "7#5",
+        intervals: vec![PERF1, MAJ3, AUG5, MIN7],
+        chord_scale: &WHOLE_TONE,
+    };
+
+    pub static ref DOMINANT_SEVENTH_FLAT_FIVE: ChordQuality = ChordQuality {
+        name: "dominant seventh flat five",
+        symbol: "7b5",
+        intervals: vec![PERF1, MAJ3, TRITONE5, MIN7],
+        chord_scale: &WHOLE_TONE,
+    };
+
+    pub static ref AUGMENTED_MAJOR_SEVENTH: ChordQuality = ChordQuality {
+        name: "augmented major seventh",
+        symbol: "maj7#5",
+        intervals: vec![PERF1, MAJ3, AUG5, MAJ7],
+        chord_scale: &LYDIAN_AUGMENTED,
+    };
+
+    // Every chord quality above, for anything that needs to iterate the whole catalog.
+    pub static ref ALL_CHORD_QUALITIES: Vec<&'static ChordQuality> = vec![
+        &MAJOR_TRIAD,
+        &MINOR_TRIAD,
+        &DIMINISHED_TRIAD,
+        &AUGMENTED_TRIAD,
+        &SUS2,
+        &SUS4,
+        &MAJOR_SIXTH,
+        &MINOR_SIXTH,
+        &MAJOR_SEVENTH,
+        &DOMINANT_SEVENTH,
+        &MINOR_SEVENTH,
+        &HALF_DIMINISHED_SEVENTH,
+        &DIMINISHED_SEVENTH,
+        &MINOR_MAJOR_SEVENTH,
+        &DOMINANT_SEVENTH_SUS4,
+        &AUGMENTED_SEVENTH,
+        &DOMINANT_SEVENTH_FLAT_FIVE,
+        &AUGMENTED_MAJOR_SEVENTH,
+    ];
+}
+
+impl ChordQuality {
+    // The quality in ALL_CHORD_QUALITIES with exactly these intervals above the root, in the same
+    // order, if there is one.
+    pub fn from_intervals(intervals: &[Interval]) -> Option<&'static ChordQuality> {
+        ALL_CHORD_QUALITIES
+            .iter()
+            .find(|quality| quality.intervals == intervals)
+            .copied()
+    }
+}
+
+// ChordSymbol is a chord as it's written on a lead sheet: a root, a quality, any tensions
+// written after the quality, and an optional bass note after a slash, like "Bbmaj7#11",
+// "C7(b9,b13)" or "C/E".
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChordSymbol {
+    pub root: Note,
+    pub quality: &'static ChordQuality,
+
+    // Tensions in the order they were written, including any implied by the quality spelling
+    // (a "C13" is a dominant seventh with a 9 and a 13).
+    pub tensions: Vec<Interval>,
+
+    // Whether the symbol was written as "alt". The specific altered tensions are left up to the
+    // player, so we don't add any to `tensions`.
+    pub altered: bool,
+
+    // The note after the slash, if any. It can be a chord tone, making the chord an inversion
+    // (C/E), or any other note (D/C). Like the root, it's in the default octave.
+    pub bass: Option<Note>,
+}
+
+impl std::fmt::Display for ChordSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Tensions that a quality spelling implies are written that way, so C7 with a 9 and a 13
+        // is C13 rather than C7(9,13).
+        let extension = QUALITY_SPELLINGS.iter().find(|spelling| {
+            spelling.quality == self.quality
+                && !spelling.implied_tensions.is_empty()
+                && spelling.implied_tensions.len() == self.tensions.len()
+                && spelling
+                    .implied_tensions
+                    .iter()
+                    .all(|tension| self.tensions.contains(tension))
+        });
+        match extension {
+            Some(spelling) => write!(f, "{}{}", self.root.name(), spelling.spelling)?,
+            None => write!(f, "{}{}", self.root.name(), self.quality.symbol)?,
+        }
+
+        if self.altered {
+            write!(f, "alt")?;
+        }
+
+        if extension.is_none() && !self.tensions.is_empty() {
+            let tensions: Vec<String> = self
+                .tensions
+                .iter()
+                .map(|tension| {
+                    tension
+                        .tension_name()
+                        .unwrap_or_else(|| tension.to_string())
+                })
+                .collect();
+            write!(f, "({})", tensions.join(","))?;
+        }
+
+        if let Some(bass) = self.bass {
+            write!(f, "/{}", bass.name())?;
+        }
+
+        Ok(())
+    }
+}
+
+// All errors carry the 0-indexed character position of the offending part of the symbol.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChordSymbolParseError {
+    // The symbol doesn't start with a note name, like "H7" or "bb7".
+    InvalidRoot,
+    // Nothing we know of (a quality, tension, "alt", or parenthesized list) starts here.
+    UnexpectedCharacter { position: usize, found: char },
+    // A tension is written here, but it isn't one of b9, 9, #9, 11, #11, b13, or 13.
+    InvalidTension { position: usize },
+    // The parenthesis here is never closed.
+    UnclosedParenthesis { position: usize },
+    // "alt" is written here on a chord that isn't a dominant seventh. A bare "alt" right after
+    // the root, like "Galt", is taken as shorthand for "G7alt".
+    AlteredNonDominant { position: usize },
+    // The slash here isn't followed by a note name, or something comes after the bass note.
+    InvalidBass { position: usize },
+}
+
+struct QualitySpelling {
+    spelling: &'static str,
+    quality: &'static ChordQuality,
+    implied_tensions: Vec<Interval>,
+}
+
+impl QualitySpelling {
+    fn new(spelling: &'static str, quality: &'static ChordQuality) -> Self {
+        QualitySpelling {
+            spelling,
+            quality,
+            implied_tensions: vec![],
+        }
+    }
+
+    fn with_tensions(
+        spelling: &'static str,
+        quality: &'static ChordQuality,
+        implied_tensions: Vec<Interval>,
+    ) -> Self {
+        QualitySpelling {
+            spelling,
+            quality,
+            implied_tensions,
+        }
+    }
+}
+
+lazy_static! {
+    static ref CHORD_ROOT_REGEX: Regex = Regex::new(&format!("^{NOTE_NAME_PATTERN}")).unwrap();
+
+    // Every way we accept a quality being written. When several spellings match the start of a
+    // symbol, the longest one wins, so "m7b5" beats "m7" and "maj7" beats "m". When a chord is
+    // displayed, the first spelling with its quality and tensions is the one used, so C7 with a 9
+    // is written "C9".
+    static ref QUALITY_SPELLINGS: Vec<QualitySpelling> = vec![
+        QualitySpelling::new("", &MAJOR_TRIAD),
+        QualitySpelling::new("M", &MAJOR_TRIAD),
+        QualitySpelling::new("maj", &MAJOR_TRIAD),
+        QualitySpelling::with_tensions("add9", &MAJOR_TRIAD, vec![T9]),
+        QualitySpelling::new("m", &MINOR_TRIAD),
+        QualitySpelling::new("-", &MINOR_TRIAD),
+        QualitySpelling::new("mi", &MINOR_TRIAD),
+        QualitySpelling::new("min", &MINOR_TRIAD),
+        QualitySpelling::with_tensions("madd9", &MINOR_TRIAD, vec![T9]),
+        QualitySpelling::new("dim", &DIMINISHED_TRIAD),
+        QualitySpelling::new("°", &DIMINISHED_TRIAD),
+        QualitySpelling::new("o", &DIMINISHED_TRIAD),
+        QualitySpelling::new("aug", &AUGMENTED_TRIAD),
+        QualitySpelling::new("+", &AUGMENTED_TRIAD),
+        QualitySpelling::new("sus2", &SUS2),
+        QualitySpelling::new("sus", &SUS4),
+        QualitySpelling::new("sus4", &SUS4),
+        QualitySpelling::new("6", &MAJOR_SIXTH),
+        QualitySpelling::new("M6", &MAJOR_SIXTH),
+        QualitySpelling::new("maj6", &MAJOR_SIXTH),
+        QualitySpelling::with_tensions("69", &MAJOR_SIXTH, vec![T9]),
+        QualitySpelling::with_tensions("6/9", &MAJOR_SIXTH, vec![T9]),
+        QualitySpelling::new("m6", &MINOR_SIXTH),
+        QualitySpelling::new("-6", &MINOR_SIXTH),
+        QualitySpelling::new("min6", &MINOR_SIXTH),
+        QualitySpelling::with_tensions("m69", &MINOR_SIXTH, vec![T9]),
+        QualitySpelling::with_tensions("m6/9", &MINOR_SIXTH, vec![T9]),
+        QualitySpelling::new("maj7", &MAJOR_SEVENTH),
+        QualitySpelling::new("Maj7", &MAJOR_SEVENTH),
+        QualitySpelling::new("ma7", &MAJOR_SEVENTH),
+        QualitySpelling::new("M7", &MAJOR_SEVENTH),
+        QualitySpelling::new("Δ", &MAJOR_SEVENTH),
+        QualitySpelling::new("Δ7", &MAJOR_SEVENTH),
+        QualitySpelling::new("^7", &MAJOR_SEVENTH),
+        QualitySpelling::with_tensions("maj9", &MAJOR_SEVENTH, vec![T9]),
+        QualitySpelling::with_tensions("M9", &MAJOR_SEVENTH, vec![T9]),
+        QualitySpelling::with_tensions("Δ9", &MAJOR_SEVENTH, vec![T9]),
+        QualitySpelling::with_tensions("maj13", &MAJOR_SEVENTH, vec![T9, T13]),
+        QualitySpelling::with_tensions("M13", &MAJOR_SEVENTH, vec![T9, T13]),
+        QualitySpelling::with_tensions("Δ13", &MAJOR_SEVENTH, vec![T9, T13]),
+        QualitySpelling::new("7", &DOMINANT_SEVENTH),
+        QualitySpelling::with_tensions("9", &DOMINANT_SEVENTH, vec![T9]),
+        QualitySpelling::with_tensions("11", &DOMINANT_SEVENTH, vec![T9, T11]),
+        QualitySpelling::with_tensions("13", &DOMINANT_SEVENTH, vec![T9, T13]),
+        QualitySpelling::new("m7", &MINOR_SEVENTH),
+        QualitySpelling::new("-7", &MINOR_SEVENTH),
+        QualitySpelling::new("mi7", &MINOR_SEVENTH),
+        QualitySpelling::new("min7", &MINOR_SEVENTH),
+        QualitySpelling::with_tensions("m9", &MINOR_SEVENTH, vec![T9]),
+        QualitySpelling::with_tensions("-9", &MINOR_SEVENTH, vec![T9]),
+        QualitySpelling::with_tensions("min9", &MINOR_SEVENTH, vec![T9]),
+        QualitySpelling::with_tensions("m11", &MINOR_SEVENTH, vec![T9, T11]),
+        QualitySpelling::with_tensions("-11", &MINOR_SEVENTH, vec![T9, T11]),
+        QualitySpelling::with_tensions("min11", &MINOR_SEVENTH, vec![T9, T11]),
+        QualitySpelling::with_tensions("m13", &MINOR_SEVENTH, vec![T9, T13]),
+        QualitySpelling::with_tensions("-13", &MINOR_SEVENTH, vec![T9, T13]),
+        QualitySpelling::with_tensions("min13", &MINOR_SEVENTH, vec![T9, T13]),
+        QualitySpelling::new("m7b5", &HALF_DIMINISHED_SEVENTH),
+        QualitySpelling::new("-7b5", &HALF_DIMINISHED_SEVENTH),
+        QualitySpelling::new("min7b5", &HALF_DIMINISHED_SEVENTH),
+        QualitySpelling::new("ø", &HALF_DIMINISHED_SEVENTH),
+        QualitySpelling::new("ø7", &HALF_DIMINISHED_SEVENTH),
+        QualitySpelling::new("dim7", &DIMINISHED_SEVENTH),
+        QualitySpelling::new("°7", &DIMINISHED_SEVENTH),
+        QualitySpelling::new("o7", &DIMINISHED_SEVENTH),
+        QualitySpelling::new("mMaj7", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("mmaj7", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("mM7", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("m(maj7)", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("minMaj7", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("-maj7", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("mΔ", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("mΔ7", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("-Δ", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("-Δ7", &MINOR_MAJOR_SEVENTH),
+        QualitySpelling::new("7sus", &DOMINANT_SEVENTH_SUS4),
+        QualitySpelling::new("7sus4", &DOMINANT_SEVENTH_SUS4),
+        QualitySpelling::with_tensions("9sus4", &DOMINANT_SEVENTH_SUS4, vec![T9]),
+        QualitySpelling::with_tensions("9sus", &DOMINANT_SEVENTH_SUS4, vec![T9]),
+        QualitySpelling::new("7#5", &AUGMENTED_SEVENTH),
+        QualitySpelling::new("7+5", &AUGMENTED_SEVENTH),
+        QualitySpelling::new("7+", &AUGMENTED_SEVENTH),
+        QualitySpelling::new("+7", &AUGMENTED_SEVENTH),
+        QualitySpelling::new("aug7", &AUGMENTED_SEVENTH),
+        QualitySpelling::new("7b5", &DOMINANT_SEVENTH_FLAT_FIVE),
+        QualitySpelling::new("7-5", &DOMINANT_SEVENTH_FLAT_FIVE),
+        QualitySpelling::new("maj7#5", &AUGMENTED_MAJOR_SEVENTH),
+        QualitySpelling::new("maj7+5", &AUGMENTED_MAJOR_SEVENTH),
+        QualitySpelling::new("+maj7", &AUGMENTED_MAJOR_SEVENTH),
+        QualitySpelling::new("Δ#5", &AUGMENTED_MAJOR_SEVENTH),
+        QualitySpelling::new("Δ+", &AUGMENTED_MAJOR_SEVENTH),
+    ];
+}
+
+impl FromStr for ChordSymbol {
+    type Err = ChordSymbolParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let root_match = CHORD_ROOT_REGEX
+            .captures(s)
+            .ok_or(ChordSymbolParseError::InvalidRoot)?;
+        let root = Note::from_name_captures(&root_match, 4);
+
+        // From here on we work in characters rather than bytes, so that positions in errors line
+        // up with what the user typed even when the symbol has a Δ or ø in it.
+        let chars: Vec<char> = s.chars().collect();
+        let mut position = s[..root_match.get(0).unwrap().end()].chars().count();
+
+        let spelling = QUALITY_SPELLINGS
+            .iter()
+            .filter(|candidate| starts_with(&chars[position..], candidate.spelling))
+            .max_by_key(|candidate| candidate.spelling.chars().count())
+            .unwrap();
+        position += spelling.spelling.chars().count();
+
+        let mut symbol = ChordSymbol {
+            root,
+            quality: spelling.quality,
+            tensions: vec![],
+            altered: false,
+            bass: None,
+        };
+        for tension in spelling.implied_tensions.iter() {
+            symbol.add_tension(*tension);
+        }
+
+        while position < chars.len() {
+            let rest = &chars[position..];
+
+            if starts_with(rest, "alt") {
+                if spelling.spelling.is_empty() && symbol.tensions.is_empty() && !symbol.altered {
+                    symbol.quality = &DOMINANT_SEVENTH;
+                }
+                if symbol.quality != &*DOMINANT_SEVENTH {
+                    return Err(ChordSymbolParseError::AlteredNonDominant { position });
+                }
+                symbol.altered = true;
+                position += 3;
+            } else if rest[0] == '(' {
+                let close = rest
+                    .iter()
+                    .position(|c| *c == ')')
+                    .ok_or(ChordSymbolParseError::UnclosedParenthesis { position })?;
+
+                // Tensions inside parentheses are separated by commas or spaces, and can be
+                // written with "add", like "C(add9)".
+                let mut inner = position + 1;
+                while inner < position + close {
+                    if chars[inner] == ',' || chars[inner] == ' ' {
+                        inner += 1;
+                        continue;
+                    }
+                    if starts_with(&chars[inner..position + close], "add") {
+                        inner += 3;
+                        continue;
+                    }
+                    let (tension, length) = parse_tension(&chars[..position + close], inner)?;
+                    symbol.add_tension(tension);
+                    inner += length;
+                }
+
+                position += close + 1;
+            } else if starts_with(rest, "add") {
+                let (tension, length) = parse_tension(&chars, position + 3)?;
+                symbol.add_tension(tension);
+                position += 3 + length;
+            } else if rest[0] == '/' {
+                // The bass note has to be the last thing in the symbol.
+                let invalid = ChordSymbolParseError::InvalidBass { position };
+                let bass: String = rest[1..].iter().collect();
+                let bass_match = CHORD_ROOT_REGEX.captures(&bass).ok_or(invalid)?;
+                if bass_match.get(0).unwrap().end() != bass.len() {
+                    return Err(ChordSymbolParseError::InvalidBass { position });
+                }
+
+                symbol.bass = Some(Note::from_name_captures(&bass_match, 4));
+                position = chars.len();
+            } else if is_tension_start(rest[0]) {
+                let (tension, length) = parse_tension(&chars, position)?;
+                symbol.add_tension(tension);
+                position += length;
+            } else {
+                return Err(ChordSymbolParseError::UnexpectedCharacter {
+                    position,
+                    found: rest[0],
+                });
+            }
+        }
+
+        Ok(symbol)
+    }
+}
+
+impl ChordSymbol {
+    // Spells the chord with its tensions placed above the chord tones, as compound intervals
+    // from the root: C7(b9,b13) is C4 E4 G4 Bb4 Db5 Ab5.
+    //
+    // A bass note goes in the root's octave, so it's on the piano diagrams, with the rest of the
+    // chord raised by octaves to sit above it: C/E is E4 G4 C5, Fmaj7/C is C4 F4 A4 E5 and
+    // G7(b9)/F is F4 G4 B4 D5 Ab5.
+    pub fn notes(&self) -> Vec<Note> {
+        self.try_notes()
+            .unwrap_or_else(|err| panic!("Couldn't spell {self}: {err:?}"))
+    }
+
+    // Like notes, but returns an error instead of panicking if any note would need more than a
+    // double accidental, like the 3rd of B##7.
+    pub fn try_notes(&self) -> Result<Vec<Note>, ApplyIntervalError> {
+        let mut notes = self.root.try_chord(self.quality)?;
+
+        for tension in self.tensions.iter() {
+            notes.push(self.root.try_apply_interval(&tension.to_compound())?);
+        }
+
+        let bass = match self.bass {
+            Some(bass) => bass,
+            None => return Ok(notes),
+        };
+        let bass = bass.with_octave(self.root.octave());
+        let same_pitch_class =
+            |note: &Note| note.intra_octave_semitone_value() == bass.intra_octave_semitone_value();
+
+        // Chord tones are raised above the bass and then sorted, and tensions are raised above
+        // everything before them, so they stay on top.
+        let above = |mut note: Note, floor: &Note| {
+            while note.inter_octave_semitone_value() <= floor.inter_octave_semitone_value() {
+                note = note.with_octave(note.octave() + 1);
+            }
+            note
+        };
+        let mut chord_tones: Vec<Note> = notes[..self.quality.intervals.len()]
+            .iter()
+            .filter(|note| !same_pitch_class(note))
+            .map(|note| above(*note, &bass))
+            .collect();
+        chord_tones.sort();
+
+        let mut voiced = vec![bass];
+        voiced.extend(chord_tones);
+        for tension in notes[self.quality.intervals.len()..].iter() {
+            if !same_pitch_class(tension) {
+                voiced.push(above(*tension, voiced.last().unwrap()));
+            }
+        }
+
+        Ok(voiced)
+    }
+
+    // The chord with the given chord tone in the bass: 0 for root position, 1 for first
+    // inversion (3rd in the bass), and so on. None if the chord doesn't have that many notes.
+    pub fn with_inversion(&self, inversion: usize) -> Option<ChordSymbol> {
+        let interval = self.quality.intervals.get(inversion)?;
+        let bass = match inversion {
+            0 => None,
+            _ => {
+                let bass = self.root.try_apply_interval(interval).ok()?;
+                Some(Note::new(bass.letter(), bass.accidental()))
+            }
+        };
+
+        Some(ChordSymbol {
+            bass,
+            ..self.clone()
+        })
+    }
+
+    // Which chord tone is in the bass, numbered like with_inversion. None if the bass isn't a
+    // chord tone, like in D/C.
+    pub fn inversion(&self) -> Option<usize> {
+        let bass = match self.bass {
+            Some(bass) => bass,
+            None => return Some(0),
+        };
+        let semitones = (bass.intra_octave_semitone_value()
+            - self.root.intra_octave_semitone_value())
+        .rem_euclid(12);
+
+        self.quality
+            .intervals
+            .iter()
+            .position(|interval| interval.semitones % 12 == semitones)
+    }
+
+    fn add_tension(&mut self, tension: Interval) {
+        if !self.tensions.contains(&tension) {
+            self.tensions.push(tension);
+        }
+    }
+}
+
+fn starts_with(chars: &[char], prefix: &str) -> bool {
+    let prefix: Vec<char> = prefix.chars().collect();
+    chars.starts_with(&prefix)
+}
+
+fn is_tension_start(c: char) -> bool {
+    matches!(c, 'b' | '♭' | '-' | '#' | '♯' | '+') || c.is_ascii_digit()
+}
+
+// Parses a single tension, like "b9" or "#11", starting at chars[start]. Returns the tension and
+// how many characters it took up.
+fn parse_tension(chars: &[char], start: usize) -> Result<(Interval, usize), ChordSymbolParseError> {
+    let invalid = ChordSymbolParseError::InvalidTension { position: start };
+
+    let mut end = start;
+    let alteration = match chars.get(end) {
+        Some('b' | '♭' | '-') => -1,
+        Some('#' | '♯' | '+') => 1,
+        _ => 0,
+    };
+    if alteration != 0 {
+        end += 1;
+    }
+
+    let digits_start = end;
+    while end < chars.len() && chars[end].is_ascii_digit() {
+        end += 1;
+    }
+    let number: String = chars[digits_start..end].iter().collect();
+
+    let tension = match (alteration, number.as_str()) {
+        (-1, "9") => TFLAT9,
+        (0, "9") => T9,
+        (1, "9") => TSHARP9,
+        (0, "11") => T11,
+        (1, "11") => TSHARP11,
+        (-1, "13") => TFLAT13,
+        (0, "13") => T13,
+        _ => return Err(invalid),
+    };
+
+    Ok((tension, end - start))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::theory::note::{Note, NoteLetter};
+
+    use super::*;
+
+    fn spell(root: &str, quality: &ChordQuality) -> Vec<String> {
+        Note::from_str(root)
+            .unwrap()
+            .chord(quality)
+            .iter()
+            .map(|note| note.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_seventh_chords() {
+        assert_eq!(spell("C", &MAJOR_SEVENTH), ["C4", "E4", "G4", "B4"]);
+        assert_eq!(spell("G", &DOMINANT_SEVENTH), ["G4", "B4", "D5", "F5"]);
+        assert_eq!(spell("Db", &MINOR_SEVENTH), ["Db4", "Fb4", "Ab4", "Cb5"]);
+        assert_eq!(
+            spell("F#", &HALF_DIMINISHED_SEVENTH),
+            ["F#4", "A4", "C5", "E5"]
+        );
+        assert_eq!(spell("B", &DIMINISHED_SEVENTH), ["B4", "D5", "F5", "Ab5"]);
+        assert_eq!(
+            spell("Eb", &MINOR_MAJOR_SEVENTH),
+            ["Eb4", "Gb4", "Bb4", "D5"]
+        );
+        assert_eq!(spell("C", &AUGMENTED_SEVENTH), ["C4", "E4", "G#4", "Bb4"]);
+    }
+
+    #[test]
+    fn test_triads_and_sixths() {
+        assert_eq!(spell("Eb", &AUGMENTED_TRIAD), ["Eb4", "G4", "B4"]);
+        assert_eq!(spell("A", &SUS4), ["A4", "D5", "E5"]);
+        assert_eq!(spell("Bb", &MINOR_SIXTH), ["Bb4", "Db5", "F5", "G5"]);
+    }
+
+    #[test]
+    fn test_parse_chord_symbols() {
+        let bbmaj7 = ChordSymbol::from_str("Bbmaj7#11").unwrap();
+        assert_eq!(bbmaj7.root.name(), "Bb");
+        assert_eq!(bbmaj7.quality, &*MAJOR_SEVENTH);
+        assert_eq!(bbmaj7.tensions, vec![TSHARP11]);
+
+        let fsm7b5 = ChordSymbol::from_str("F#m7b5").unwrap();
+        assert_eq!(fsm7b5.root.name(), "F#");
+        assert_eq!(fsm7b5.quality, &*HALF_DIMINISHED_SEVENTH);
+
+        let galt = ChordSymbol::from_str("G7alt").unwrap();
+        assert_eq!(galt.quality, &*DOMINANT_SEVENTH);
+        assert!(galt.altered);
+
+        let c7 = ChordSymbol::from_str("C7(b9,b13)").unwrap();
+        assert_eq!(c7.quality, &*DOMINANT_SEVENTH);
+        assert_eq!(c7.tensions, vec![TFLAT9, TFLAT13]);
+    }
+
+    #[test]
+    fn test_parse_quality_spellings() {
+        let quality_of = |s: &str| ChordSymbol::from_str(s).unwrap().quality;
+
+        assert_eq!(quality_of("CΔ"), &*MAJOR_SEVENTH);
+        assert_eq!(quality_of("CM7"), &*MAJOR_SEVENTH);
+        assert_eq!(quality_of("C-7"), &*MINOR_SEVENTH);
+        assert_eq!(quality_of("Cø"), &*HALF_DIMINISHED_SEVENTH);
+        assert_eq!(quality_of("C°7"), &*DIMINISHED_SEVENTH);
+        assert_eq!(quality_of("Csus"), &*SUS4);
+        assert_eq!(quality_of("C7sus4"), &*DOMINANT_SEVENTH_SUS4);
+        assert_eq!(quality_of("Cm(maj7)"), &*MINOR_MAJOR_SEVENTH);
+        assert_eq!(quality_of("C"), &*MAJOR_TRIAD);
+
+        let c13 = ChordSymbol::from_str("C13").unwrap();
+        assert_eq!(c13.quality, &*DOMINANT_SEVENTH);
+        assert_eq!(c13.tensions, vec![T9, T13]);
+    }
+
+    #[test]
+    fn test_chord_symbol_errors() {
+        assert_eq!(
+            ChordSymbol::from_str("H7"),
+            Err(ChordSymbolParseError::InvalidRoot)
+        );
+        assert_eq!(
+            ChordSymbol::from_str("Cmaj7x"),
+            Err(ChordSymbolParseError::UnexpectedCharacter {
+                position: 5,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            ChordSymbol::from_str("Δ"),
+            Err(ChordSymbolParseError::InvalidRoot)
+        );
+        assert_eq!(
+            ChordSymbol::from_str("C7(b9,#13)"),
+            Err(ChordSymbolParseError::InvalidTension { position: 6 })
+        );
+        assert_eq!(
+            ChordSymbol::from_str("C7(b9"),
+            Err(ChordSymbolParseError::UnclosedParenthesis { position: 2 })
+        );
+        assert_eq!(
+            ChordSymbol::from_str("Cm7alt"),
+            Err(ChordSymbolParseError::AlteredNonDominant { position: 3 })
+        );
+        assert_eq!(
+            ChordSymbol::from_str("Cmajalt"),
+            Err(ChordSymbolParseError::AlteredNonDominant { position: 4 })
+        );
+        assert_eq!(
+            ChordSymbol::from_str("C(add#13)"),
+            Err(ChordSymbolParseError::InvalidTension { position: 5 })
+        );
+    }
+
+    #[test]
+    fn test_chord_symbol_display() {
+        for symbol in ["Bbmaj7(#11)", "F#m7b5", "G7alt", "C7(b9,b13)", "Eb"] {
+            assert_eq!(ChordSymbol::from_str(symbol).unwrap().to_string(), symbol);
+        }
+
+        // Extensions are written the way the quality spelling implies them.
+        for symbol in ["C13", "Cmaj9", "Cadd9", "C9sus4", "Cm11", "C69", "Cmadd9"] {
+            assert_eq!(ChordSymbol::from_str(symbol).unwrap().to_string(), symbol);
+        }
+        let display = |s: &str| ChordSymbol::from_str(s).unwrap().to_string();
+        assert_eq!(display("C7(9,13)"), "C13");
+        assert_eq!(display("C(add9)"), "Cadd9");
+        assert_eq!(display("C9sus"), "C9sus4");
+        assert_eq!(display("C7(9,#11,13)"), "C7(9,#11,13)");
+
+        // A bare "alt" is a dominant seventh.
+        assert_eq!(
+            ChordSymbol::from_str("Galt").unwrap(),
+            ChordSymbol::from_str("G7alt").unwrap()
+        );
+        assert_eq!(display("Galt"), "G7alt");
+        assert_eq!(display("Galt/Db"), "G7alt/Db");
+    }
+
+    #[test]
+    fn test_chord_symbol_notes() {
+        let notes: Vec<String> = ChordSymbol::from_str("C7(b9,b13)")
+            .unwrap()
+            .notes()
+            .iter()
+            .map(|note| note.to_string())
+            .collect();
+        assert_eq!(notes, ["C4", "E4", "G4", "Bb4", "Db5", "Ab5"]);
+
+        // The 3rd of B##7 would be D with a triple sharp.
+        assert_eq!(
+            ChordSymbol::from_str("B##7").unwrap().try_notes(),
+            Err(ApplyIntervalError::TooManyAccidentals {
+                letter: NoteLetter::D,
+                offset: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_slash_chords() {
+        let notes = |symbol: &str| -> Vec<String> {
+            ChordSymbol::from_str(symbol)
+                .unwrap()
+                .notes()
+                .iter()
+                .map(|note| note.to_string())
+                .collect()
+        };
+
+        let c_over_e = ChordSymbol::from_str("C/E").unwrap();
+        assert_eq!(c_over_e.bass, Some(Note::from_str("E").unwrap()));
+        assert_eq!(c_over_e.inversion(), Some(1));
+        // The bass is always in the root's octave, whichever letter it's on.
+        assert_eq!(notes("C/E"), ["E4", "G4", "C5"]);
+        assert_eq!(notes("Eb/G"), ["G4", "Bb4", "Eb5"]);
+        assert_eq!(notes("Fmaj7/C"), ["C4", "F4", "A4", "E5"]);
+        assert_eq!(notes("Cm7/Bb"), ["Bb4", "C5", "Eb5", "G5"]);
+        assert_eq!(notes("G7(b9)/F"), ["F4", "G4", "B4", "D5", "Ab5"]);
+
+        // So is a bass that isn't a chord tone, and one that doubles a tension replaces it.
+        let d_over_c = ChordSymbol::from_str("D/C").unwrap();
+        assert_eq!(d_over_c.inversion(), None);
+        assert_eq!(notes("D/C"), ["C4", "D4", "F#4", "A4"]);
+        assert_eq!(notes("Bb/Ab"), ["Ab4", "Bb4", "D5", "F5"]);
+        assert_eq!(notes("C/D"), ["D4", "E4", "G4", "C5"]);
+        assert_eq!(notes("C7(9)/D"), ["D4", "E4", "G4", "Bb4", "C5"]);
+
+        // "6/9" is still a quality, not a bass note.
+        let c69 = ChordSymbol::from_str("C6/9").unwrap();
+        assert_eq!(c69.bass, None);
+        assert_eq!(c69.tensions, vec![T9]);
+        assert_eq!(
+            ChordSymbol::from_str("C6/9/E").unwrap().bass,
+            Some(Note::from_str("E").unwrap())
+        );
+
+        for symbol in ["C/E", "Bbm9/Ab", "G7alt/Db"] {
+            assert_eq!(ChordSymbol::from_str(symbol).unwrap().to_string(), symbol);
+        }
+
+        assert_eq!(
+            ChordSymbol::from_str("C/H"),
+            Err(ChordSymbolParseError::InvalidBass { position: 1 })
+        );
+        assert_eq!(
+            ChordSymbol::from_str("Cm7/Bb7"),
+            Err(ChordSymbolParseError::InvalidBass { position: 3 })
+        );
+    }
+
+    #[test]
+    fn test_inversions() {
+        let fmaj7 = ChordSymbol::from_str("Fmaj7").unwrap();
+        assert_eq!(fmaj7.inversion(), Some(0));
+        assert_eq!(fmaj7.with_inversion(0).unwrap(), fmaj7);
+        assert_eq!(fmaj7.with_inversion(2).unwrap().to_string(), "Fmaj7/C");
+        assert_eq!(fmaj7.with_inversion(3).unwrap().to_string(), "Fmaj7/E");
+        assert_eq!(fmaj7.with_inversion(4), None);
+
+        let ab = ChordSymbol::from_str("Ab").unwrap();
+        assert_eq!(ab.with_inversion(1).unwrap().to_string(), "Ab/C");
+        assert_eq!(ab.with_inversion(2).unwrap().inversion(), Some(2));
+    }
+
+    #[test]
+    fn test_catalog_is_stacked_from_the_root() {
+        for quality in ALL_CHORD_QUALITIES.iter() {
+            assert_eq!(quality.intervals[0], PERF1, "{} has no root", quality.name);
+        }
+    }
+
+    #[test]
+    fn test_quality_from_intervals() {
+        assert_eq!(
+            ChordQuality::from_intervals(&[PERF1, MIN3, TRITONE5, MIN7]),
+            Some(&*HALF_DIMINISHED_SEVENTH)
+        );
+        assert_eq!(
+            ChordQuality::from_intervals(&[PERF1, MAJ3, PERF5]),
+            Some(&*MAJOR_TRIAD)
+        );
+        assert_eq!(ChordQuality::from_intervals(&[PERF1, MAJ3, TRITONE5]), None);
+
+        for quality in ALL_CHORD_QUALITIES.iter() {
+            assert_eq!(
+                ChordQuality::from_intervals(&quality.intervals),
+                Some(*quality)
+            );
+        }
+    }
+}
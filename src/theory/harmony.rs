@@ -0,0 +1,301 @@
+use crate::theory::chord::{ChordQuality, ChordSymbol};
+use crate::theory::interval::{Interval, OCT1};
+use crate::theory::note::{ApplyIntervalError, Note, NoteAccidental, NoteLetter};
+use crate::theory::roman::RomanNumeral;
+use crate::theory::scale::{Scale, IONIAN};
+
+// How many thirds to stack on each scale degree when harmonizing a scale.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChordSize {
+    Triad,
+    Seventh,
+    Ninth,
+    Eleventh,
+    Thirteenth,
+}
+
+impl ChordSize {
+    fn num_notes(&self) -> usize {
+        match self {
+            ChordSize::Triad => 3,
+            ChordSize::Seventh => 4,
+            ChordSize::Ninth => 5,
+            ChordSize::Eleventh => 6,
+            ChordSize::Thirteenth => 7,
+        }
+    }
+}
+
+// A chord built by stacking thirds from one of the scale's notes, using only notes in the scale.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DiatonicChord {
+    // The scale degree the chord is built on, from 1 to 7.
+    pub degree: usize,
+
+    // The chord tones, from the root up. Everything past the 7th is in the octave above, so the
+    // 9th of a chord is spelled a 9th above its root.
+    pub notes: Vec<Note>,
+
+    // The interval from the root to each note in `notes`.
+    pub intervals: Vec<Interval>,
+
+    // The quality of the triad or seventh chord at the bottom of the stack. This is None if it
+    // isn't one of ALL_CHORD_QUALITIES, like a major triad with a flatted 5th.
+    pub quality: Option<&'static ChordQuality>,
+
+    // The 9th, 11th and 13th of the chord, if they were stacked, written within the octave like
+    // the tension constants.
+    pub tensions: Vec<Interval>,
+
+    // How far the root is from the same degree of the major scale, in semitones, so the root of
+    // the III chord in dorian is -1.
+    degree_offset: i8,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HarmonizeError {
+    // Stacking thirds only makes sense for 7-note scales; stacking them in a pentatonic or bebop
+    // scale skips or doubles up on letters.
+    NotHeptatonic { num_notes: usize },
+    // One of the chord tones needs more than a double accidental.
+    Unspellable(ApplyIntervalError),
+}
+
+impl From<ApplyIntervalError> for HarmonizeError {
+    fn from(err: ApplyIntervalError) -> Self {
+        HarmonizeError::Unspellable(err)
+    }
+}
+
+// Stacks thirds on every degree of the scale starting on root, returning the 7 chords from the
+// I chord up. For example, triads in C major are C, Dm, Em, F, G, Am and Bdim.
+pub fn harmonize(
+    root: &Note,
+    scale: &Scale,
+    size: ChordSize,
+) -> Result<Vec<DiatonicChord>, HarmonizeError> {
+    let num_degrees = scale.ascending.len();
+    if num_degrees != 7 {
+        return Err(HarmonizeError::NotHeptatonic {
+            num_notes: num_degrees,
+        });
+    }
+
+    let scale_notes = root.try_ascending_scale(scale)?;
+
+    let mut chords = Vec::with_capacity(num_degrees);
+    for degree in 0..num_degrees {
+        let mut notes = Vec::with_capacity(size.num_notes());
+        for third in 0..size.num_notes() {
+            let index = degree + 2 * third;
+
+            let mut note = scale_notes[index % num_degrees];
+            for _ in 0..index / num_degrees {
+                note = note.try_apply_interval(&OCT1)?;
+            }
+
+            // Scales that use a letter twice, like the b9 and #9 of the altered scale, don't
+            // stack into thirds as written, so those notes are respelled: the #9 is a b3 in the
+            // I chord of C altered.
+            let letter = NoteLetter::ALL[(scale_notes[degree].letter() as usize + 2 * third) % 7];
+            if note.letter() != letter {
+                note = note
+                    .respell(letter)
+                    .map_err(|_| too_many_accidentals(&note, letter))?;
+            }
+            notes.push(note);
+        }
+
+        // Every interval is ascending and spans at most 12 letters, so it always has a name.
+        let intervals: Vec<Interval> = notes
+            .iter()
+            .map(|note| notes[0].interval_to(note).unwrap())
+            .collect();
+
+        let num_chord_tones = size.num_notes().min(4);
+        let tensions = intervals[num_chord_tones..]
+            .iter()
+            .map(|interval| interval.to_simple())
+            .collect();
+
+        chords.push(DiatonicChord {
+            degree: degree + 1,
+            quality: ChordQuality::from_intervals(&intervals[..num_chord_tones]),
+            notes,
+            intervals,
+            tensions,
+            degree_offset: scale.ascending[degree].semitones - IONIAN.ascending[degree].semitones,
+        });
+    }
+
+    Ok(chords)
+}
+
+// The error for spelling the note with the given letter, when that would take more than a double
+// accidental.
+fn too_many_accidentals(note: &Note, letter: NoteLetter) -> ApplyIntervalError {
+    let natural = Note::new(letter, NoteAccidental::None);
+    let mut offset =
+        (note.intra_octave_semitone_value() - natural.intra_octave_semitone_value()).rem_euclid(12);
+    if offset > 6 {
+        offset -= 12;
+    }
+    ApplyIntervalError::TooManyAccidentals { letter, offset }
+}
+
+impl DiatonicChord {
+    pub fn root(&self) -> Note {
+        self.notes[0]
+    }
+
+    // The chord as a lead sheet symbol, like "Dm7(9,11)", if its quality is known.
+    pub fn chord_symbol(&self) -> Option<ChordSymbol> {
+        self.quality.map(|quality| ChordSymbol {
+            root: self.root(),
+            quality,
+            tensions: self.tensions.clone(),
+            altered: false,
+            bass: None,
+        })
+    }
+
+    // The roman numeral of the chord, if its quality is known. Degrees are numbered against the
+    // major scale, so the chord on the 3rd of dorian is bIII, and the triads of C major are I, ii,
+    // iii, IV, V, vi and vii°. Tensions aren't included.
+    pub fn roman_numeral(&self) -> Option<RomanNumeral> {
+        Some(RomanNumeral {
+            alteration: self.degree_offset,
+            degree: self.degree,
+            quality: self.quality?,
+            tritone_substitute: false,
+            target: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::theory::chord::*;
+    use crate::theory::interval::*;
+    use crate::theory::scale::*;
+
+    use super::*;
+
+    fn numerals(root: &str, scale: &Scale, size: ChordSize) -> Vec<String> {
+        harmonize(&Note::from_str(root).unwrap(), scale, size)
+            .unwrap()
+            .iter()
+            .map(|chord| chord.roman_numeral().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_major_triads() {
+        let chords = harmonize(&Note::from_str("C").unwrap(), &IONIAN, ChordSize::Triad).unwrap();
+
+        let symbols: Vec<String> = chords
+            .iter()
+            .map(|chord| chord.chord_symbol().unwrap().to_string())
+            .collect();
+        assert_eq!(symbols, ["C", "Dm", "Em", "F", "G", "Am", "Bdim"]);
+        assert_eq!(
+            numerals("C", &IONIAN, ChordSize::Triad),
+            ["I", "ii", "iii", "IV", "V", "vi", "vii°"]
+        );
+
+        let b_dim: Vec<String> = chords[6].notes.iter().map(|n| n.to_string()).collect();
+        assert_eq!(b_dim, ["B4", "D5", "F5"]);
+    }
+
+    #[test]
+    fn test_seventh_chords() {
+        assert_eq!(
+            numerals("Eb", &IONIAN, ChordSize::Seventh),
+            ["Imaj7", "ii7", "iii7", "IVmaj7", "V7", "vi7", "viiø7"]
+        );
+        assert_eq!(
+            numerals("A", &HARMONIC_MINOR, ChordSize::Seventh),
+            [
+                "imaj7",
+                "iiø7",
+                "bIII+maj7",
+                "iv7",
+                "V7",
+                "bVImaj7",
+                "vii°7"
+            ]
+        );
+        assert_eq!(
+            numerals("C", &MELODIC_MINOR, ChordSize::Seventh),
+            ["imaj7", "ii7", "bIII+maj7", "IV7", "V7", "viø7", "viiø7"]
+        );
+
+        let d_dorian =
+            harmonize(&Note::from_str("D").unwrap(), &DORIAN, ChordSize::Seventh).unwrap();
+        assert_eq!(d_dorian[3].quality, Some(&*DOMINANT_SEVENTH));
+        assert_eq!(d_dorian[3].root(), Note::from_str("G").unwrap());
+        assert_eq!(
+            numerals("D", &DORIAN, ChordSize::Triad),
+            ["i", "ii", "bIII", "IV", "v", "vi°", "bVII"]
+        );
+    }
+
+    #[test]
+    fn test_extended_chords() {
+        let chords = harmonize(
+            &Note::from_str("C").unwrap(),
+            &IONIAN,
+            ChordSize::Thirteenth,
+        )
+        .unwrap();
+
+        assert_eq!(chords[1].quality, Some(&*MINOR_SEVENTH));
+        assert_eq!(chords[1].tensions, vec![T9, T11, T13]);
+        assert_eq!(chords[1].intervals[4], MAJ9);
+        assert_eq!(chords[2].tensions, vec![TFLAT9, T11, TFLAT13]);
+        assert_eq!(chords[3].tensions, vec![T9, TSHARP11, T13]);
+        assert_eq!(chords[4].chord_symbol().unwrap().to_string(), "G7(9,11,13)");
+
+        let top: Vec<String> = chords[0].notes.iter().map(|n| n.to_string()).collect();
+        assert_eq!(top, ["C4", "E4", "G4", "B4", "D5", "F5", "A5"]);
+    }
+
+    #[test]
+    fn test_scales_with_repeated_letters() {
+        // C altered spells D# and Db, but its chords are still stacked in thirds.
+        let chords =
+            harmonize(&Note::from_str("C").unwrap(), &ALTERED, ChordSize::Seventh).unwrap();
+        let symbols: Vec<String> = chords
+            .iter()
+            .map(|chord| chord.chord_symbol().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            symbols,
+            ["Cm7b5", "DbmMaj7", "D#m7", "Emaj7#5", "Gb7", "Ab7", "Bbm7b5"]
+        );
+
+        let c_half_dim: Vec<String> = chords[0].notes.iter().map(|n| n.to_string()).collect();
+        assert_eq!(c_half_dim, ["C4", "Eb4", "Gb4", "Bb4"]);
+    }
+
+    #[test]
+    fn test_unsupported_scales() {
+        let c = Note::from_str("C").unwrap();
+        assert_eq!(
+            harmonize(&c, &BEBOP_DOMINANT, ChordSize::Triad),
+            Err(HarmonizeError::NotHeptatonic { num_notes: 8 })
+        );
+        assert_eq!(
+            harmonize(&c, &MAJOR_PENTATONIC, ChordSize::Seventh),
+            Err(HarmonizeError::NotHeptatonic { num_notes: 5 })
+        );
+
+        let fbb = Note::from_str("Fbb").unwrap();
+        assert!(matches!(
+            harmonize(&fbb, &LOCRIAN, ChordSize::Triad),
+            Err(HarmonizeError::Unspellable(_))
+        ));
+    }
+}
@@ -0,0 +1,525 @@
+use crate::theory::chord::{ChordQuality, ChordSymbol, ALL_CHORD_QUALITIES};
+use crate::theory::interval::*;
+use crate::theory::note::{Note, SpellingPreference};
+use crate::theory::scale::{Scale, ALL_SCALES};
+
+// One way of naming a set of notes as a chord. identify_chord returns these best first.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChordCandidate {
+    // The chord, with any notes that aren't chord tones as tensions, and the lowest note as its
+    // bass unless the chord is in root position. The root and bass are in the default octave,
+    // like a parsed chord symbol.
+    pub symbol: ChordSymbol,
+
+    // Which chord tone is in the bass: 0 for root position, 1 for first inversion (3rd in the
+    // bass), and so on. None if a tension is in the bass.
+    pub inversion: Option<usize>,
+
+    // Whether the perfect 5th of the chord is missing, which is common in voicings of seventh
+    // chords.
+    pub omits_fifth: bool,
+
+    // How unlikely this reading is; lower is better. See identify_chord.
+    score: u32,
+}
+
+// Names the chord formed by the given notes, which can be in any order and any octaves. Each
+// note has to be a chord tone or a tension of the candidate, and every chord tone has to be
+// present, except the perfect 5th of a chord with four or more notes.
+//
+// Candidates are ranked by how a musician would most likely read the notes: root position over
+// inversions, fewer tensions over more, and readings that match how the notes are spelled over
+// ones that don't. So C E G A is C6 before Am7 in first inversion, and G B D F Ab is G7(b9). Notes
+// that don't form a chord, like C C# D, return no candidates.
+pub fn identify_chord(notes: &[Note]) -> Vec<ChordCandidate> {
+    let bass = match notes.iter().min() {
+        Some(bass) => *bass,
+        None => return vec![],
+    };
+
+    // One note per pitch class, keeping the lowest spelling of each.
+    let mut sorted = notes.to_vec();
+    sorted.sort();
+    let mut distinct: Vec<Note> = vec![];
+    for note in sorted {
+        if !distinct.iter().any(|other| same_pitch_class(other, &note)) {
+            distinct.push(note);
+        }
+    }
+
+    let mut candidates: Vec<ChordCandidate> = vec![];
+    for root in distinct.iter() {
+        for quality in ALL_CHORD_QUALITIES.iter() {
+            if let Some(candidate) = match_quality(root, quality, &distinct, &bass) {
+                candidates.push(candidate);
+            }
+        }
+    }
+
+    // This is a stable sort, so equally likely readings stay ordered by root, starting from the
+    // bass, and then in the order of ALL_CHORD_QUALITIES.
+    candidates.sort_by_key(|candidate| candidate.score);
+    candidates
+}
+
+// Reads the notes as a chord of the given quality on root, if every note fits. If one of the
+// chord tones or tensions can't be spelled from root, like the 3rd of B##, the root is respelled
+// the simplest way instead, so B## is read as C#.
+fn match_quality(
+    root: &Note,
+    quality: &'static ChordQuality,
+    notes: &[Note],
+    bass: &Note,
+) -> Option<ChordCandidate> {
+    let semitones_above_root = |note: &Note| {
+        (note.intra_octave_semitone_value() - root.intra_octave_semitone_value()).rem_euclid(12)
+    };
+
+    let mut score = 0;
+    let mut tensions = vec![];
+    let mut intervals = vec![];
+    for note in notes {
+        let semitones = semitones_above_root(note);
+        let interval = match quality
+            .intervals
+            .iter()
+            .find(|interval| interval.semitones % 12 == semitones)
+        {
+            Some(chord_tone) => *chord_tone,
+            None => {
+                let tension = tension_for(semitones)?;
+                tensions.push(tension);
+                score += 1;
+
+                // An 11 against a major 3rd clashes, so it's an unlikely reading.
+                if tension == T11 && quality.intervals.contains(&MAJ3) {
+                    score += 2;
+                }
+                tension
+            }
+        };
+        intervals.push(interval);
+    }
+
+    let spellable = |root: &Note| {
+        intervals
+            .iter()
+            .chain(quality.intervals.iter())
+            .all(|interval| root.try_apply_interval(interval).is_ok())
+    };
+    let root = if spellable(root) {
+        *root
+    } else {
+        score += 1;
+        Some(root.simplest_spelling()).filter(spellable)?
+    };
+
+    // Penalize readings that disagree with how the note is spelled, like calling an Ab the #5 of
+    // a C chord.
+    for (note, interval) in notes.iter().zip(intervals.iter()) {
+        if root.apply_interval(interval).letter() != note.letter() {
+            score += 1;
+        }
+    }
+
+    let present = |interval: &Interval| {
+        notes
+            .iter()
+            .any(|note| semitones_above_root(note) == interval.semitones % 12)
+    };
+
+    let mut omits_fifth = false;
+    for interval in quality.intervals.iter() {
+        if present(interval) {
+            continue;
+        }
+
+        if *interval == PERF5 && quality.intervals.len() >= 4 {
+            omits_fifth = true;
+            score += 1;
+        } else {
+            return None;
+        }
+    }
+
+    let bass_semitones = semitones_above_root(bass);
+    let inversion = quality
+        .intervals
+        .iter()
+        .position(|interval| interval.semitones % 12 == bass_semitones);
+    score += match inversion {
+        Some(0) => 0,
+        Some(_) => 2,
+        None => 4,
+    };
+
+    tensions.sort_by_key(|tension| tension.semitones);
+
+    Some(ChordCandidate {
+        symbol: ChordSymbol {
+            root: Note::new(root.letter(), root.accidental()),
+            quality,
+            tensions,
+            altered: false,
+            bass: match inversion {
+                Some(0) => None,
+                _ => Some(Note::new(bass.letter(), bass.accidental())),
+            },
+        },
+        inversion,
+        omits_fifth,
+        score,
+    })
+}
+
+// The tension a note is when it's this many semitones above the root (within the octave), if it
+// can be one at all.
+fn tension_for(semitones: i8) -> Option<Interval> {
+    match semitones {
+        1 => Some(TFLAT9),
+        2 => Some(T9),
+        3 => Some(TSHARP9),
+        5 => Some(T11),
+        6 => Some(TSHARP11),
+        8 => Some(TFLAT13),
+        9 => Some(T13),
+        _ => None,
+    }
+}
+
+// A scale from the catalog, on some root, that contains a given set of notes. identify_scales and
+// chord_scales return these best first.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ScaleCandidate {
+    // The root of the scale, in the default octave.
+    pub root: Note,
+    pub scale: &'static Scale,
+
+    // How many notes of the scale weren't given. The fewer there are, the closer the fit.
+    pub extra_notes: usize,
+
+    // How unlikely this scale is; lower is better. See rank_scales.
+    score: u32,
+}
+
+impl std::fmt::Display for ScaleCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.root.name(), self.scale.name)
+    }
+}
+
+// Lists every scale in the catalog, on every root, that contains all of the given notes. Scales
+// with the fewest notes that weren't given come first, and among those, scales rooted on the
+// lowest given note.
+pub fn identify_scales(notes: &[Note]) -> Vec<ScaleCandidate> {
+    match notes.iter().min() {
+        Some(lowest) => rank_scales(notes, lowest, None),
+        None => vec![],
+    }
+}
+
+// Suggests scales to play over the chord, in the spirit of "the LAST thing I'm thinking about on
+// G ALT is G! That's Ab melodic minor": every scale that contains the chord tones and tensions.
+// The chord's default chord-scale on its root comes first when it fits, so Dm7 gives D dorian
+// ahead of D minor pentatonic. After that it's closest fit first, and among equally close fits,
+// scales on the chord root. So G7alt, whose tensions rule out mixolydian, gives G altered,
+// followed by the other modes with the same notes, like Ab melodic minor.
+//
+// An alt chord is taken to have all four altered tensions (b9, #9, #11 and b13) and no perfect
+// 5th, since the #11 and b13 replace it. Chords with a note that needs more than a double
+// accidental, like B##7alt, have no candidates.
+pub fn chord_scales(symbol: &ChordSymbol) -> Vec<ScaleCandidate> {
+    let root = &symbol.root;
+
+    let mut intervals: Vec<Interval> = symbol
+        .quality
+        .intervals
+        .iter()
+        .filter(|interval| !(symbol.altered && **interval == PERF5))
+        .copied()
+        .collect();
+    intervals.extend(symbol.tensions.iter());
+    if symbol.altered {
+        intervals.extend([TFLAT9, TSHARP9, TSHARP11, TFLAT13]);
+    }
+
+    let notes: Result<Vec<Note>, _> = intervals
+        .iter()
+        .map(|interval| root.try_apply_interval(interval))
+        .collect();
+    match notes {
+        Ok(notes) => rank_scales(&notes, root, Some(symbol.quality.chord_scale)),
+        Err(_) => vec![],
+    }
+}
+
+// Finds every scale containing the notes. preferred_scale on preferred_root comes first if it
+// contains them; after that, each missing note outweighs everything else, then scales rooted on
+// preferred_root, then preferred_scale.
+fn rank_scales(
+    notes: &[Note],
+    preferred_root: &Note,
+    preferred_scale: Option<&Scale>,
+) -> Vec<ScaleCandidate> {
+    let mut pitch_classes: Vec<i8> = notes
+        .iter()
+        .map(|note| note.intra_octave_semitone_value())
+        .collect();
+    pitch_classes.sort();
+    pitch_classes.dedup();
+
+    let mut candidates = vec![];
+    for pitch_class in 0..12 {
+        for scale in ALL_SCALES.iter() {
+            // The chromatic scale is the same on every root, so only list it once.
+            if scale.ascending.len() == 12
+                && pitch_class != preferred_root.intra_octave_semitone_value()
+            {
+                continue;
+            }
+
+            let scale_pitch_classes: Vec<i8> = scale
+                .ascending
+                .iter()
+                .map(|interval| (pitch_class + interval.semitones).rem_euclid(12))
+                .collect();
+            if !pitch_classes
+                .iter()
+                .all(|pitch_class| scale_pitch_classes.contains(pitch_class))
+            {
+                continue;
+            }
+
+            let root = match spell_scale_root(pitch_class, scale, notes) {
+                Some(root) => root,
+                None => continue,
+            };
+
+            let extra_notes = scale.ascending.len() - pitch_classes.len();
+            let on_root = same_pitch_class(&root, preferred_root);
+            let mut score = 0;
+            if !on_root || preferred_scale != Some(*scale) {
+                score += 1 + 4 * extra_notes as u32;
+                if !on_root {
+                    score += 2;
+                }
+                if preferred_scale != Some(*scale) {
+                    score += 1;
+                }
+            }
+
+            candidates.push(ScaleCandidate {
+                root,
+                scale,
+                extra_notes,
+                score,
+            });
+        }
+    }
+
+    // Stable, so ties stay ordered by root from C and then in the order of ALL_SCALES.
+    candidates.sort_by_key(|candidate| candidate.score);
+    candidates
+}
+
+// Picks the spelling of the root that spells the most given notes the same way the scale does,
+// and after that, the one with the fewest accidentals: Ab melodic minor rather than G# melodic
+// minor over G7alt. Returns None if the scale can't be spelled from any root with this pitch class.
+fn spell_scale_root(pitch_class: i8, scale: &Scale, notes: &[Note]) -> Option<Note> {
+    let mut spellings: Vec<Note> = [SpellingPreference::Sharps, SpellingPreference::Flats]
+        .iter()
+        .map(|preference| Note::from_midi(60 + pitch_class as u8, *preference))
+        .collect();
+    spellings.extend(
+        notes
+            .iter()
+            .filter(|note| note.intra_octave_semitone_value() == pitch_class)
+            .map(|note| Note::new(note.letter(), note.accidental())),
+    );
+
+    spellings
+        .iter()
+        .filter_map(|root| {
+            let scale_notes = root.try_ascending_scale(scale).ok()?;
+            let matches = notes
+                .iter()
+                .filter(|note| scale_notes.iter().any(|other| other.name() == note.name()))
+                .count();
+            let accidentals: i32 = scale_notes
+                .iter()
+                .map(|note| note.accidental().semitone_offset().abs() as i32)
+                .sum();
+
+            Some((root, matches as i32, accidentals))
+        })
+        .min_by_key(|(_, matches, accidentals)| (-matches, *accidentals))
+        .map(|(root, _, _)| *root)
+}
+
+fn same_pitch_class(a: &Note, b: &Note) -> bool {
+    a.intra_octave_semitone_value() == b.intra_octave_semitone_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::theory::chord::*;
+    use crate::theory::scale::MIXO;
+
+    use super::*;
+
+    fn identify(notes: &str) -> Vec<ChordCandidate> {
+        let notes: Vec<Note> = notes
+            .split_whitespace()
+            .map(|note| Note::from_str(note).unwrap())
+            .collect();
+        identify_chord(&notes)
+    }
+
+    fn best(notes: &str) -> String {
+        identify(notes)[0].symbol.to_string()
+    }
+
+    #[test]
+    fn test_root_position() {
+        assert_eq!(best("C4 E4 G4"), "C");
+        assert_eq!(best("D4 F4 A4 C5"), "Dm7");
+        assert_eq!(best("B3 D4 F4 A4"), "Bm7b5");
+        assert_eq!(best("Gb3 Bb3 Db4 F4"), "Gbmaj7");
+        assert_eq!(best("C4 F4 G4"), "Csus4");
+
+        let c = &identify("C4 E4 G4")[0];
+        assert_eq!(c.symbol, ChordSymbol::from_str("C").unwrap());
+        assert_eq!(c.inversion, Some(0));
+        assert!(!c.omits_fifth);
+    }
+
+    #[test]
+    fn test_inversions() {
+        let first = &identify("E4 G4 C5")[0];
+        assert_eq!(first.symbol.to_string(), "C/E");
+        assert_eq!(first.inversion, Some(1));
+
+        let third = &identify("F3 G3 B3 D4")[0];
+        assert_eq!(third.symbol.to_string(), "G7/F");
+        assert_eq!(third.inversion, Some(3));
+
+        // Any order and any octave.
+        assert_eq!(best("G5 C3 E6 C4"), "C");
+    }
+
+    #[test]
+    fn test_tensions_and_omitted_fifths() {
+        let c9 = &identify("C3 E3 Bb3 D4")[0];
+        assert_eq!(c9.symbol.to_string(), "C9");
+        assert!(c9.omits_fifth);
+
+        assert_eq!(best("G3 B3 D4 F4 Ab4"), "G7(b9)");
+        assert_eq!(best("F3 A3 E4 G4 B4"), "Fmaj7(9,#11)");
+        assert_eq!(best("D3 F3 C4 E4 G4"), "Dm11");
+    }
+
+    #[test]
+    fn test_ambiguous_chords() {
+        let readings: Vec<String> = identify("C4 E4 G4 A4")
+            .iter()
+            .map(|candidate| candidate.symbol.to_string())
+            .collect();
+        assert_eq!(readings[0], "C6");
+        assert!(readings.contains(&"Am7/C".to_string()));
+
+        // Diminished seventh chords are symmetric, so the bass decides.
+        let dim7 = identify("B3 D4 F4 Ab4");
+        assert_eq!(dim7[0].symbol.to_string(), "Bdim7");
+        assert_eq!(
+            dim7.iter()
+                .filter(|candidate| candidate.symbol.quality == &*DIMINISHED_SEVENTH)
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_unspellable_roots() {
+        // The major 3rd of B## would be D with a triple sharp, so it's read on C# instead.
+        let c_sharp = &identify("B##3 E#4 G#4")[0];
+        assert_eq!(c_sharp.symbol.to_string(), "C#");
+        assert_eq!(c_sharp.inversion, Some(0));
+        assert!(c_sharp.symbol.try_notes().is_ok());
+    }
+
+    #[test]
+    fn test_non_chords() {
+        assert!(identify("").is_empty());
+        assert!(identify("C4 C#4 D4").is_empty());
+
+        // A triad needs its 5th.
+        assert!(!identify("C4 E4")
+            .iter()
+            .any(|candidate| candidate.symbol.quality == &*MAJOR_TRIAD));
+    }
+
+    fn scales(notes: &str) -> Vec<String> {
+        let notes: Vec<Note> = notes
+            .split_whitespace()
+            .map(|note| Note::from_str(note).unwrap())
+            .collect();
+        identify_scales(&notes)
+            .iter()
+            .map(|candidate| candidate.to_string())
+            .collect()
+    }
+
+    fn suggestions(symbol: &str) -> Vec<String> {
+        chord_scales(&ChordSymbol::from_str(symbol).unwrap())
+            .iter()
+            .map(|candidate| candidate.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_identify_scales() {
+        let c_major = scales("C4 D4 E4 F4 G4 A4 B4");
+        assert_eq!(c_major[0], "C ionian");
+        assert!(c_major[..7].contains(&"D dorian".to_string()));
+        assert!(c_major[..7].contains(&"B locrian".to_string()));
+
+        assert_eq!(scales("A3 B3 C4 D4 E4 F4 G#4")[0], "A harmonic minor");
+        assert_eq!(scales("Eb4 F4 Gb4 Ab4 Bb4 C5 D5")[0], "Eb melodic minor");
+        assert_eq!(scales("C4 D4 E4 G4 A4")[0], "C major pentatonic");
+
+        // Only the chromatic scale has all 12 notes.
+        let chromatic = scales("C4 C#4 D4 D#4 E4 F4 F#4 G4 G#4 A4 A#4 B4");
+        assert_eq!(chromatic, ["C chromatic"]);
+
+        let candidates = identify_scales(&[Note::from_str("C4").unwrap()]);
+        assert!(candidates
+            .iter()
+            .all(|candidate| candidate.extra_notes == candidate.scale.ascending.len() - 1));
+    }
+
+    #[test]
+    fn test_chord_scales() {
+        let alt = suggestions("G7alt");
+        assert_eq!(alt[0], "G altered");
+        assert!(alt[..7].contains(&"Ab melodic minor".to_string()));
+
+        // The default chord-scale comes first, even though pentatonics leave out fewer notes.
+        let dm7 = suggestions("Dm7");
+        assert_eq!(dm7[0], "D dorian");
+        assert_eq!(dm7[1], "D minor pentatonic");
+        let position = |name: &str| dm7.iter().position(|scale| scale == name).unwrap();
+        assert!(position("D blues") < position("D aeolian"));
+        assert_eq!(suggestions("Cmaj7#11")[0], "C lydian");
+        assert_eq!(suggestions("C7(b9,#9,13)")[0], "C half-whole diminished");
+        assert_eq!(suggestions("Bm7b5(11,b13)")[0], "B locrian");
+
+        let c7 = chord_scales(&ChordSymbol::from_str("C7").unwrap());
+        assert_eq!(c7[0].scale, &*MIXO);
+        assert_eq!(c7[0].extra_notes, 3);
+
+        // The 3rd of B##7 would be D with a triple sharp.
+        assert!(chord_scales(&ChordSymbol::from_str("B##7alt").unwrap()).is_empty());
+    }
+}
@@ -1,14 +1,22 @@
-#[derive(Debug)]
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum IntervalQuality {
     Perfect,
     Major,
     Minor,
     Augmented,
     Diminished,
+    DoublyAugmented,
+    DoublyDiminished,
 }
 
-// Interval is only ascending, for now.
-#[derive(Debug)]
+// An Interval is a distance without a direction: Note::apply_interval moves up by it, and
+// Note::apply_interval_below moves down by it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Interval {
     pub quality: IntervalQuality,
 
@@ -18,120 +26,550 @@ pub struct Interval {
     pub semitones: i8,
 }
 
-pub const PERF1: Interval = Interval {
-    quality: IntervalQuality::Perfect,
-    number: 0,
-    semitones: 0,
-};
-pub const MIN2: Interval = Interval {
-    quality: IntervalQuality::Minor,
-    number: 1,
-    semitones: 1,
-};
-pub const MAJ2: Interval = Interval {
-    quality: IntervalQuality::Major,
-    number: 1,
-    semitones: 2,
-};
-
-pub const AUG2: Interval = Interval {
-    quality: IntervalQuality::Augmented,
-    number: 1,
-    semitones: 3,
-};
-pub const MIN3: Interval = Interval {
-    quality: IntervalQuality::Minor,
-    number: 2,
-    semitones: 3,
-};
-pub const MAJ3: Interval = Interval {
-    quality: IntervalQuality::Major,
-    number: 2,
-    semitones: 4,
-};
-pub const PERF4: Interval = Interval {
-    quality: IntervalQuality::Perfect,
-    number: 3,
-    semitones: 5,
-};
-pub const TRITONE4: Interval = Interval {
-    quality: IntervalQuality::Augmented,
-    number: 3,
-    semitones: 6,
-};
-pub const TRITONE5: Interval = Interval {
-    quality: IntervalQuality::Diminished,
-    number: 4,
-    semitones: 6,
-};
-pub const PERF5: Interval = Interval {
-    quality: IntervalQuality::Perfect,
-    number: 4,
-    semitones: 7,
-};
-pub const MIN6: Interval = Interval {
-    quality: IntervalQuality::Minor,
-    number: 5,
-    semitones: 8,
-};
-pub const MAJ6: Interval = Interval {
-    quality: IntervalQuality::Major,
-    number: 5,
-    semitones: 9,
-};
-pub const MIN7: Interval = Interval {
-    quality: IntervalQuality::Minor,
-    number: 6,
-    semitones: 10,
-};
-pub const MAJ7: Interval = Interval {
-    quality: IntervalQuality::Major,
-    number: 6,
-    semitones: 11,
-};
-pub const OCT1: Interval = Interval {
-    quality: IntervalQuality::Perfect,
-    number: 7,
-    semitones: 12,
-};
+#[derive(Debug, PartialEq, Eq)]
+pub enum IntervalError {
+    // The second note is lower than the first, but intervals only go up.
+    Descending,
+    // The semitones are too far from the number of letters spanned for any quality we can name,
+    // like a triply augmented 2nd.
+    UnnamedQuality { number: i8, semitones: i8 },
+    // The notes are so far apart that the interval's number or semitones don't fit in an i8.
+    TooWide,
+}
+
+impl Interval {
+    // Like from_number_and_semitones, but panics if the quality can't be named. This is meant for
+    // constants, where the panic happens at compile time.
+    pub const fn new(number: i8, semitones: i8) -> Interval {
+        match Interval::from_number_and_semitones(number, semitones) {
+            Ok(interval) => interval,
+            Err(_) => panic!("Interval constants must have a nameable quality"),
+        }
+    }
+
+    // Builds the interval spanning `number` note letters (0-indexed, like the `number` field) and
+    // `semitones` semitones, deriving its quality. Numbers of 7 and up are compound intervals.
+    pub const fn from_number_and_semitones(
+        number: i8,
+        semitones: i8,
+    ) -> Result<Interval, IntervalError> {
+        if number < 0 {
+            return Err(IntervalError::Descending);
+        }
+
+        let quality = match (
+            is_perfect_number(number),
+            semitones - reference_semitones(number),
+        ) {
+            (true, 0) => IntervalQuality::Perfect,
+            (false, 0) => IntervalQuality::Major,
+            (false, -1) => IntervalQuality::Minor,
+            (_, 1) => IntervalQuality::Augmented,
+            (_, 2) => IntervalQuality::DoublyAugmented,
+            (true, -1) | (false, -2) => IntervalQuality::Diminished,
+            (true, -2) | (false, -3) => IntervalQuality::DoublyDiminished,
+            _ => return Err(IntervalError::UnnamedQuality { number, semitones }),
+        };
+
+        Ok(Interval {
+            quality,
+            number,
+            semitones,
+        })
+    }
+
+    // The interval's name written out, like "minor third" or "perfect eleventh".
+    pub fn long_name(&self) -> String {
+        let quality = match self.quality {
+            IntervalQuality::Perfect => "perfect",
+            IntervalQuality::Major => "major",
+            IntervalQuality::Minor => "minor",
+            IntervalQuality::Augmented => "augmented",
+            IntervalQuality::Diminished => "diminished",
+            IntervalQuality::DoublyAugmented => "doubly augmented",
+            IntervalQuality::DoublyDiminished => "doubly diminished",
+        };
+
+        match ORDINALS.get(self.number as usize) {
+            Some(ordinal) => format!("{quality} {ordinal}"),
+            None => format!("{quality} {}th", self.number + 1),
+        }
+    }
+
+    // The interval's name as a tension, like "b9" or "#11", if it's a 2nd, 4th or 6th (or the 9th,
+    // 11th or 13th they stand in for) within a half step of major or perfect.
+    pub fn tension_name(&self) -> Option<String> {
+        let tension_number = match self.number % 7 {
+            1 => 9,
+            3 => 11,
+            5 => 13,
+            _ => return None,
+        };
+
+        let accidental = match self.semitones - reference_semitones(self.number) {
+            -1 => "b",
+            0 => "",
+            1 => "#",
+            _ => return None,
+        };
+
+        Some(format!("{accidental}{tension_number}"))
+    }
+
+    // Inverts the interval within the octave, so a major 3rd becomes a minor 6th and an augmented
+    // 4th becomes a diminished 5th. Compound intervals are reduced first; unisons and octaves
+    // invert into each other.
+    pub fn invert(&self) -> Interval {
+        let simple = if self.number == 7 {
+            *self
+        } else {
+            self.to_simple()
+        };
+
+        let quality = match simple.quality {
+            IntervalQuality::Perfect => IntervalQuality::Perfect,
+            IntervalQuality::Major => IntervalQuality::Minor,
+            IntervalQuality::Minor => IntervalQuality::Major,
+            IntervalQuality::Augmented => IntervalQuality::Diminished,
+            IntervalQuality::Diminished => IntervalQuality::Augmented,
+            IntervalQuality::DoublyAugmented => IntervalQuality::DoublyDiminished,
+            IntervalQuality::DoublyDiminished => IntervalQuality::DoublyAugmented,
+        };
+
+        Interval {
+            quality,
+            number: 7 - simple.number,
+            semitones: 12 - simple.semitones,
+        }
+    }
+
+    // Stacks other on top of self, so a major 3rd plus a minor 3rd is a perfect 5th. Fails if the
+    // result has no nameable quality, like three augmented 2nds stacked on each other.
+    pub fn checked_add(&self, other: &Interval) -> Result<Interval, IntervalError> {
+        Interval::from_number_and_semitones(
+            self.number + other.number,
+            self.semitones + other.semitones,
+        )
+    }
+
+    // Removes other from the top of self, so a perfect 5th minus a major 3rd is a minor 3rd. Fails
+    // with IntervalError::Descending if other is the larger interval.
+    pub fn checked_sub(&self, other: &Interval) -> Result<Interval, IntervalError> {
+        Interval::from_number_and_semitones(
+            self.number - other.number,
+            self.semitones - other.semitones,
+        )
+    }
+
+    // Whether the two intervals span the same number of semitones, regardless of spelling, like
+    // TRITONE4 and TRITONE5.
+    pub fn is_enharmonic_to(&self, other: &Interval) -> bool {
+        self.semitones == other.semitones
+    }
+
+    // Whether the interval spans more than an octave, like a 9th or a 13th.
+    pub fn is_compound(&self) -> bool {
+        self.number >= 7
+    }
+
+    // The same interval an octave higher, if it isn't already compound. This is how a tension
+    // becomes the interval it's voiced at above the chord: T9 becomes MAJ9.
+    pub fn to_compound(&self) -> Interval {
+        if self.is_compound() {
+            return *self;
+        }
+
+        Interval {
+            quality: self.quality,
+            number: self.number + 7,
+            semitones: self.semitones + 12,
+        }
+    }
+
+    // The interval with any whole octaves removed, so MAJ9 becomes T9 (a major 2nd) and OCT1
+    // becomes PERF1.
+    pub fn to_simple(&self) -> Interval {
+        let octaves = self.number / 7;
+
+        Interval {
+            quality: self.quality,
+            number: self.number - 7 * octaves,
+            semitones: self.semitones - 12 * octaves,
+        }
+    }
+}
+
+// The semitones in the major or perfect interval with this number, like 4 for a 3rd.
+const fn reference_semitones(number: i8) -> i8 {
+    const MAJOR_OR_PERFECT_SEMITONES: [i8; 7] = [0, 2, 4, 5, 7, 9, 11];
+    MAJOR_OR_PERFECT_SEMITONES[(number % 7) as usize] + 12 * (number / 7)
+}
+
+// Whether intervals with this number are perfect (unisons, 4ths, 5ths and their compounds) rather
+// than major or minor.
+const fn is_perfect_number(number: i8) -> bool {
+    matches!(number % 7, 0 | 3 | 4)
+}
+
+const ORDINALS: [&str; 15] = [
+    "unison",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "octave",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+];
+
+const CARDINALS: [&str; 15] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve", "thirteen", "fourteen", "fifteen",
+];
+
+// Intervals are written with a quality letter and a 1-indexed number, like "m3", "P5" or "A4".
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let quality = match self.quality {
+            IntervalQuality::Perfect => "P",
+            IntervalQuality::Major => "M",
+            IntervalQuality::Minor => "m",
+            IntervalQuality::Augmented => "A",
+            IntervalQuality::Diminished => "d",
+            IntervalQuality::DoublyAugmented => "AA",
+            IntervalQuality::DoublyDiminished => "dd",
+        };
+
+        write!(f, "{}{}", quality, self.number + 1)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IntervalParseError {
+    InvalidFormat,
+    // The quality doesn't go with the number, like "P3" or "major fifth".
+    InvalidQuality,
+}
+
+lazy_static! {
+    static ref QUALITY_NOTATION_REGEX: Regex = Regex::new(r"^(AA|dd|P|M|m|A|d)(\d+)$").unwrap();
+    static ref DEGREE_NOTATION_REGEX: Regex = Regex::new(r"^(bb|b|♭♭|♭|##|#|♯♯|♯)?(\d+)$").unwrap();
+}
+
+// Intervals can be parsed from three notations:
+//
+//  - Quality letter and number, like "m3", "P5", "A4", "d7" or "M9".
+//  - Scale degree or tension, like "b3", "#4", "b9", "#11" or "13". Following the tension constants,
+//    9ths, 11ths and 13ths written this way stay within the octave, so "b9" is TFLAT9.
+//  - Long names, like "minor third", "augmented fourth", "octave" or "sharp eleven".
+impl FromStr for Interval {
+    type Err = IntervalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some(captures) = QUALITY_NOTATION_REGEX.captures(s) {
+            let quality = match &captures[1] {
+                "P" => IntervalQuality::Perfect,
+                "M" => IntervalQuality::Major,
+                "m" => IntervalQuality::Minor,
+                "A" => IntervalQuality::Augmented,
+                "d" => IntervalQuality::Diminished,
+                "AA" => IntervalQuality::DoublyAugmented,
+                _ => IntervalQuality::DoublyDiminished,
+            };
+            return from_quality_and_degree(quality, &captures[2]);
+        }
+
+        if let Some(captures) = DEGREE_NOTATION_REGEX.captures(s) {
+            let alteration = match captures.get(1).map_or("", |m| m.as_str()) {
+                "bb" | "♭♭" => -2,
+                "b" | "♭" => -1,
+                "#" | "♯" => 1,
+                "##" | "♯♯" => 2,
+                _ => 0,
+            };
+            return from_alteration_and_degree(alteration, &captures[2]);
+        }
+
+        from_long_name(s)
+    }
+}
+
+// Parses the 1-indexed degree written in a symbol, rejecting 0 and anything past a 15th.
+fn parse_degree(degree: &str) -> Result<i8, IntervalParseError> {
+    match degree.parse::<i8>() {
+        Ok(degree @ 1..=15) => Ok(degree - 1),
+        _ => Err(IntervalParseError::InvalidFormat),
+    }
+}
+
+fn from_quality_and_degree(
+    quality: IntervalQuality,
+    degree: &str,
+) -> Result<Interval, IntervalParseError> {
+    let number = parse_degree(degree)?;
+    let offset = match (is_perfect_number(number), quality) {
+        (true, IntervalQuality::Perfect) | (false, IntervalQuality::Major) => 0,
+        (false, IntervalQuality::Minor) => -1,
+        (_, IntervalQuality::Augmented) => 1,
+        (_, IntervalQuality::DoublyAugmented) => 2,
+        (true, IntervalQuality::Diminished) => -1,
+        (false, IntervalQuality::Diminished) => -2,
+        (true, IntervalQuality::DoublyDiminished) => -2,
+        (false, IntervalQuality::DoublyDiminished) => -3,
+        _ => return Err(IntervalParseError::InvalidQuality),
+    };
+
+    Interval::from_number_and_semitones(number, reference_semitones(number) + offset)
+        .map_err(|_| IntervalParseError::InvalidQuality)
+}
+
+fn from_alteration_and_degree(
+    alteration: i8,
+    degree: &str,
+) -> Result<Interval, IntervalParseError> {
+    let mut number = parse_degree(degree)?;
+
+    // 9ths, 11ths and 13ths are tensions, which we keep within the octave.
+    if matches!(number, 8 | 10 | 12) {
+        number -= 7;
+    }
+
+    Interval::from_number_and_semitones(number, reference_semitones(number) + alteration)
+        .map_err(|_| IntervalParseError::InvalidQuality)
+}
+
+fn from_long_name(s: &str) -> Result<Interval, IntervalParseError> {
+    let lowercase = s.to_lowercase().replace('-', " ");
+    let words: Vec<&str> = lowercase.split_whitespace().collect();
+
+    let ordinal = |word: &str| ORDINALS.iter().position(|o| *o == word);
+    let cardinal = |word: &str| CARDINALS.iter().position(|c| *c == word);
+
+    match words.as_slice() {
+        ["tritone"] => Ok(TRITONE4),
+        [number] if ordinal(number).is_some() => {
+            // A bare "octave" or "unison" is perfect.
+            let number = ordinal(number).unwrap() as i8;
+            if !is_perfect_number(number) {
+                return Err(IntervalParseError::InvalidFormat);
+            }
+            Interval::from_number_and_semitones(number, reference_semitones(number))
+                .map_err(|_| IntervalParseError::InvalidQuality)
+        }
+        [quality @ .., number] if ordinal(number).is_some() => {
+            let quality = match quality {
+                ["perfect"] => IntervalQuality::Perfect,
+                ["major"] => IntervalQuality::Major,
+                ["minor"] => IntervalQuality::Minor,
+                ["augmented"] => IntervalQuality::Augmented,
+                ["diminished"] => IntervalQuality::Diminished,
+                ["doubly", "augmented"] => IntervalQuality::DoublyAugmented,
+                ["doubly", "diminished"] => IntervalQuality::DoublyDiminished,
+                _ => return Err(IntervalParseError::InvalidFormat),
+            };
+            let degree = (ordinal(number).unwrap() + 1).to_string();
+            from_quality_and_degree(quality, &degree)
+        }
+        [alteration @ .., number] if cardinal(number).is_some() => {
+            let alteration = match alteration {
+                [] | ["natural"] => 0,
+                ["flat"] => -1,
+                ["sharp"] => 1,
+                ["double", "flat"] => -2,
+                ["double", "sharp"] => 2,
+                _ => return Err(IntervalParseError::InvalidFormat),
+            };
+            let degree = (cardinal(number).unwrap() + 1).to_string();
+            from_alteration_and_degree(alteration, &degree)
+        }
+        _ => Err(IntervalParseError::InvalidFormat),
+    }
+}
+
+pub const PERF1: Interval = Interval::new(0, 0);
+pub const MIN2: Interval = Interval::new(1, 1);
+pub const MAJ2: Interval = Interval::new(1, 2);
+
+pub const AUG2: Interval = Interval::new(1, 3);
+pub const MIN3: Interval = Interval::new(2, 3);
+pub const MAJ3: Interval = Interval::new(2, 4);
+pub const DIM4: Interval = Interval::new(3, 4);
+pub const PERF4: Interval = Interval::new(3, 5);
+pub const TRITONE4: Interval = Interval::new(3, 6);
+pub const TRITONE5: Interval = Interval::new(4, 6);
+pub const PERF5: Interval = Interval::new(4, 7);
+pub const AUG5: Interval = Interval::new(4, 8);
+pub const MIN6: Interval = Interval::new(5, 8);
+pub const MAJ6: Interval = Interval::new(5, 9);
+pub const DIM7: Interval = Interval::new(6, 9);
+pub const MIN7: Interval = Interval::new(6, 10);
+pub const MAJ7: Interval = Interval::new(6, 11);
+pub const OCT1: Interval = Interval::new(7, 12);
+
+// Compound intervals
+
+pub const MIN9: Interval = Interval::new(8, 13);
+pub const MAJ9: Interval = Interval::new(8, 14);
+pub const AUG9: Interval = Interval::new(8, 15);
+pub const PERF11: Interval = Interval::new(10, 17);
+pub const AUG11: Interval = Interval::new(10, 18);
+pub const MIN13: Interval = Interval::new(12, 20);
+pub const MAJ13: Interval = Interval::new(12, 21);
 
 // Tensions
 //
 // These are kept in the same octave (i.e. `semitone` isn't >= 12) because in Jazz, a T9 doesn't
-// mean "something that's a major 9th away".
-
-pub const T9: Interval = Interval {
-    quality: IntervalQuality::Major,
-    number: 1,
-    semitones: 2,
-};
-
-pub const TFLAT9: Interval = Interval {
-    quality: IntervalQuality::Minor,
-    number: 1,
-    semitones: 1,
-};
-
-pub const T11: Interval = Interval {
-    quality: IntervalQuality::Perfect,
-    number: 3,
-    semitones: 5,
-};
-
-pub const TSHARP11: Interval = Interval {
-    quality: IntervalQuality::Augmented,
-    number: 3,
-    semitones: 6,
-};
-
-pub const T13: Interval = Interval {
-    quality: IntervalQuality::Major,
-    number: 5,
-    semitones: 9,
-};
-
-pub const TFLAT13: Interval = Interval {
-    quality: IntervalQuality::Minor,
-    number: 5,
-    semitones: 8,
-};
+// mean "something that's a major 9th away". Use `to_compound` when a tension needs to be placed
+// above the chord, like in a voicing.
+
+pub const T9: Interval = Interval::new(1, 2);
+
+pub const TFLAT9: Interval = Interval::new(1, 1);
+
+pub const TSHARP9: Interval = Interval::new(1, 3);
+
+pub const T11: Interval = Interval::new(3, 5);
+
+pub const TSHARP11: Interval = Interval::new(3, 6);
+
+pub const T13: Interval = Interval::new(5, 9);
+
+pub const TFLAT13: Interval = Interval::new(5, 8);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tensions_and_compound_intervals() {
+        let tensions = [TFLAT9, T9, TSHARP9, T11, TSHARP11, TFLAT13, T13];
+        let compounds = [MIN9, MAJ9, AUG9, PERF11, AUG11, MIN13, MAJ13];
+
+        for (tension, compound) in tensions.iter().zip(compounds.iter()) {
+            assert!(!tension.is_compound());
+            assert!(compound.is_compound());
+            assert_eq!(tension.to_compound(), *compound);
+            assert_eq!(compound.to_simple(), *tension);
+            assert_eq!(compound.to_compound(), *compound);
+        }
+
+        assert_eq!(OCT1.to_simple(), PERF1);
+    }
+
+    #[test]
+    fn test_interval_arithmetic() {
+        assert_eq!(MAJ3.invert(), MIN6);
+        assert_eq!(TRITONE4.invert(), TRITONE5);
+        assert_eq!(PERF4.invert(), PERF5);
+        assert_eq!(PERF1.invert(), OCT1);
+        assert_eq!(OCT1.invert(), PERF1);
+        assert_eq!(MAJ9.invert(), MIN7);
+        assert_eq!(DIM7.invert(), AUG2);
+
+        assert_eq!(MAJ3.checked_add(&MIN3), Ok(PERF5));
+        assert_eq!(PERF5.checked_add(&MAJ3), Ok(MAJ7));
+        assert_eq!(MAJ3.checked_add(&MAJ3), Ok(AUG5));
+        assert_eq!(OCT1.checked_add(&MAJ2), Ok(MAJ9));
+        assert_eq!(PERF5.checked_sub(&MAJ3), Ok(MIN3));
+        assert_eq!(MAJ3.checked_sub(&PERF5), Err(IntervalError::Descending));
+
+        let doubly_augmented_third = AUG2.checked_add(&AUG2).unwrap();
+        assert_eq!(
+            doubly_augmented_third.quality,
+            IntervalQuality::DoublyAugmented
+        );
+        assert_eq!(
+            doubly_augmented_third.checked_add(&AUG2),
+            Err(IntervalError::UnnamedQuality {
+                number: 3,
+                semitones: 9
+            })
+        );
+
+        assert!(TRITONE4.is_enharmonic_to(&TRITONE5));
+        assert!(AUG2.is_enharmonic_to(&MIN3));
+        assert!(!MAJ2.is_enharmonic_to(&MAJ9));
+    }
+
+    #[test]
+    fn test_constants_match_derived_qualities() {
+        assert_eq!(TRITONE4.quality, IntervalQuality::Augmented);
+        assert_eq!(TRITONE5.quality, IntervalQuality::Diminished);
+        assert_eq!(DIM4.quality, IntervalQuality::Diminished);
+        assert_eq!(TFLAT13.quality, IntervalQuality::Minor);
+        assert_eq!(OCT1.quality, IntervalQuality::Perfect);
+    }
+
+    #[test]
+    fn test_parse_intervals() {
+        let parse = |s: &str| Interval::from_str(s).unwrap();
+
+        assert_eq!(parse("m3"), MIN3);
+        assert_eq!(parse("P5"), PERF5);
+        assert_eq!(parse("A4"), TRITONE4);
+        assert_eq!(parse("d5"), TRITONE5);
+        assert_eq!(parse("d7"), DIM7);
+        assert_eq!(parse("M9"), MAJ9);
+        assert_eq!(parse("P8"), OCT1);
+
+        assert_eq!(parse("b9"), TFLAT9);
+        assert_eq!(parse("#11"), TSHARP11);
+        assert_eq!(parse("♭13"), TFLAT13);
+        assert_eq!(parse("b3"), MIN3);
+        assert_eq!(parse("5"), PERF5);
+
+        assert_eq!(parse("minor third"), MIN3);
+        assert_eq!(parse("Augmented Fourth"), TRITONE4);
+        assert_eq!(parse("octave"), OCT1);
+        assert_eq!(parse("sharp eleven"), TSHARP11);
+        assert_eq!(parse("flat nine"), TFLAT9);
+        assert_eq!(parse("thirteen"), T13);
+    }
+
+    #[test]
+    fn test_invalid_intervals() {
+        assert_eq!(
+            Interval::from_str("P3"),
+            Err(IntervalParseError::InvalidQuality)
+        );
+        assert_eq!(
+            Interval::from_str("major fifth"),
+            Err(IntervalParseError::InvalidQuality)
+        );
+        assert_eq!(
+            Interval::from_str("m0"),
+            Err(IntervalParseError::InvalidFormat)
+        );
+        assert_eq!(
+            Interval::from_str("sharpest eleven"),
+            Err(IntervalParseError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn test_display_intervals() {
+        assert_eq!(MIN3.to_string(), "m3");
+        assert_eq!(TRITONE4.to_string(), "A4");
+        assert_eq!(OCT1.to_string(), "P8");
+        assert_eq!(MAJ13.to_string(), "M13");
+        assert_eq!(MIN3.long_name(), "minor third");
+        assert_eq!(PERF11.long_name(), "perfect eleventh");
+        assert_eq!(TSHARP11.tension_name(), Some("#11".to_string()));
+        assert_eq!(MIN9.tension_name(), Some("b9".to_string()));
+        assert_eq!(PERF5.tension_name(), None);
+
+        for interval in [MIN2, AUG2, DIM4, AUG5, DIM7, MAJ9, AUG11] {
+            assert_eq!(Interval::from_str(&interval.to_string()), Ok(interval));
+            assert_eq!(Interval::from_str(&interval.long_name()), Ok(interval));
+        }
+    }
+}
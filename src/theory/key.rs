@@ -0,0 +1,406 @@
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::theory::note::{Note, NoteAccidental, NoteLetter, NOTE_NAME_PATTERN};
+use crate::theory::scale::{Scale, AEOLIAN, IONIAN};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyMode {
+    Major,
+    Minor,
+}
+
+// A major or minor key, like Gb major or C# minor. Only the 15 major and 15 minor keys whose
+// signatures have at most 7 sharps or flats exist; theoretical keys like G# major are rejected.
+//
+// Keys don't have an octave, so the tonic is always stored in the default octave.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Key {
+    tonic: Note,
+    mode: KeyMode,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeyError {
+    // The key signature would need more than 7 sharps or flats, like G# major, which would need
+    // an F double sharp. `fifths` is how many sharps (or flats, if negative) it would need.
+    TheoreticalKey { fifths: i8 },
+}
+
+// The sharps or flats written at the start of each staff in a key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeySignature {
+    // The number of sharps, or the negated number of flats, so Eb major is -3. This is also how
+    // many fifths the key is from C major.
+    pub fifths: i8,
+}
+
+// Sharps are added to key signatures in this order, and flats in the reverse order. It's also the
+// order of the note letters along the line of fifths.
+const ORDER_OF_SHARPS: [NoteLetter; 7] = [
+    NoteLetter::F,
+    NoteLetter::C,
+    NoteLetter::G,
+    NoteLetter::D,
+    NoteLetter::A,
+    NoteLetter::E,
+    NoteLetter::B,
+];
+
+impl KeySignature {
+    // Sharp, flat, or None for C major and A minor.
+    pub fn accidental(&self) -> NoteAccidental {
+        match self.fifths {
+            f if f > 0 => NoteAccidental::Sharp,
+            f if f < 0 => NoteAccidental::Flat,
+            _ => NoteAccidental::None,
+        }
+    }
+
+    // How many sharps or flats are in the signature.
+    pub fn count(&self) -> usize {
+        self.fifths.unsigned_abs() as usize
+    }
+
+    // The sharped or flatted notes in the order they're written, so A major is F#, C#, G# and
+    // Eb major is Bb, Eb, Ab.
+    pub fn notes(&self) -> Vec<Note> {
+        let letters: Vec<&NoteLetter> = if self.fifths >= 0 {
+            ORDER_OF_SHARPS.iter().take(self.count()).collect()
+        } else {
+            ORDER_OF_SHARPS.iter().rev().take(self.count()).collect()
+        };
+
+        letters
+            .into_iter()
+            .map(|letter| Note::new(*letter, self.accidental()))
+            .collect()
+    }
+
+    // The accidental the signature puts on every note with the given letter.
+    pub fn accidental_for(&self, letter: NoteLetter) -> NoteAccidental {
+        if self.notes().iter().any(|note| note.letter() == letter) {
+            self.accidental()
+        } else {
+            NoteAccidental::None
+        }
+    }
+}
+
+impl std::fmt::Display for KeySignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.count();
+        match (self.accidental(), count) {
+            (NoteAccidental::None, _) => write!(f, "no sharps or flats"),
+            (NoteAccidental::Sharp, 1) => write!(f, "1 sharp"),
+            (NoteAccidental::Flat, 1) => write!(f, "1 flat"),
+            (NoteAccidental::Sharp, _) => write!(f, "{count} sharps"),
+            _ => write!(f, "{count} flats"),
+        }
+    }
+}
+
+impl Key {
+    pub fn new(tonic: Note, mode: KeyMode) -> Result<Key, KeyError> {
+        let fifths = line_of_fifths_position(&tonic) - mode_offset(mode);
+        if !(-7..=7).contains(&fifths) {
+            return Err(KeyError::TheoreticalKey { fifths });
+        }
+
+        Ok(Key {
+            tonic: Note::new(tonic.letter(), tonic.accidental()),
+            mode,
+        })
+    }
+
+    // The key of the given mode with `fifths` sharps (or flats, if negative), so 2 and Minor is
+    // B minor.
+    pub fn from_fifths(fifths: i8, mode: KeyMode) -> Result<Key, KeyError> {
+        if !(-7..=7).contains(&fifths) {
+            return Err(KeyError::TheoreticalKey { fifths });
+        }
+
+        Key::new(note_at_position(fifths + mode_offset(mode)), mode)
+    }
+
+    pub fn tonic(&self) -> Note {
+        self.tonic
+    }
+
+    pub fn mode(&self) -> KeyMode {
+        self.mode
+    }
+
+    pub fn signature(&self) -> KeySignature {
+        KeySignature {
+            fifths: line_of_fifths_position(&self.tonic) - mode_offset(self.mode),
+        }
+    }
+
+    // The scale of the key: major, or natural minor.
+    pub fn scale(&self) -> &'static Scale {
+        match self.mode {
+            KeyMode::Major => &IONIAN,
+            KeyMode::Minor => &AEOLIAN,
+        }
+    }
+
+    // The notes of the key's scale, starting on the tonic.
+    pub fn notes(&self) -> Vec<Note> {
+        self.tonic.ascending_scale(self.scale())
+    }
+
+    // The key with the same signature and the other mode, like A minor for C major.
+    pub fn relative(&self) -> Key {
+        let mode = match self.mode {
+            KeyMode::Major => KeyMode::Minor,
+            KeyMode::Minor => KeyMode::Major,
+        };
+
+        Key::from_fifths(self.signature().fifths, mode).unwrap()
+    }
+
+    // The key with the same tonic and the other mode, like C minor for C major. Fails if that key
+    // is theoretical, like G# major for G# minor.
+    pub fn parallel(&self) -> Result<Key, KeyError> {
+        match self.mode {
+            KeyMode::Major => Key::new(self.tonic, KeyMode::Minor),
+            KeyMode::Minor => Key::new(self.tonic, KeyMode::Major),
+        }
+    }
+
+    // Spells the pitch class (0 for C through 11 for B) the way it's usually written in this key,
+    // in the default octave. Diatonic notes are spelled as in the key's scale. Chromatic notes
+    // get whichever spelling is closest to the key on the line of fifths, so C major has F# but
+    // Bb, and F# major has E# but D. Double sharps and flats are avoided, so F# major has G
+    // rather than F##.
+    //
+    // The one chromatic note equally far either way is spelled as the flatted 6th in major keys
+    // (Ab in C major) and the raised 7th in minor keys (G# in A minor).
+    pub fn spell_pitch_class(&self, pitch_class: i8) -> Note {
+        // Going up a fifth is 7 semitones, and 7 * 7 = 49 = 1 (mod 12), so this finds a position
+        // on the line of fifths with the right pitch class.
+        let position = (pitch_class.rem_euclid(12) as i16 * 7).rem_euclid(12) as i8;
+
+        // The diatonic notes of a key with f sharps are at positions f - 1 to f + 5.
+        let center = self.signature().fifths + 2;
+        let distance = match (position - center).rem_euclid(12) {
+            6 if self.mode == KeyMode::Major => -6,
+            d if d > 6 => d - 12,
+            d => d,
+        };
+
+        let closest = note_at_position(center + distance);
+        match closest.accidental() {
+            NoteAccidental::DoubleSharp => note_at_position(center + distance - 12),
+            NoteAccidental::DoubleFlat => note_at_position(center + distance + 12),
+            _ => closest,
+        }
+    }
+
+    // Respells the note the way it's usually written in this key, keeping its pitch and octave.
+    // See spell_pitch_class.
+    pub fn spell(&self, note: &Note) -> Note {
+        let letter = self
+            .spell_pitch_class(note.intra_octave_semitone_value())
+            .letter();
+
+        // Only fails for notes at the very edge of the octave range, like B#127.
+        note.respell(letter).unwrap_or(*note)
+    }
+}
+
+// Every major key followed by every minor key, each from 7 flats to 7 sharps.
+pub fn all_keys() -> Vec<Key> {
+    [KeyMode::Major, KeyMode::Minor]
+        .iter()
+        .flat_map(|mode| (-7..=7).map(|fifths| Key::from_fifths(fifths, *mode).unwrap()))
+        .collect()
+}
+
+// How far the tonic of a key is from its number of sharps on the line of fifths: C major has no
+// sharps, and A minor's tonic is 3 fifths above C.
+fn mode_offset(mode: KeyMode) -> i8 {
+    match mode {
+        KeyMode::Major => 0,
+        KeyMode::Minor => 3,
+    }
+}
+
+// Where the note's name falls on the line of fifths, ...Bb, F, C, G, D..., with C at 0. Every
+// sharp moves a note 7 positions right, and every flat 7 positions left.
+fn line_of_fifths_position(note: &Note) -> i8 {
+    let letter_position = ORDER_OF_SHARPS
+        .iter()
+        .position(|letter| *letter == note.letter())
+        .unwrap() as i8
+        - 1;
+
+    letter_position + 7 * note.accidental().semitone_offset()
+}
+
+// The inverse of line_of_fifths_position, for positions that need at most a double accidental.
+fn note_at_position(position: i8) -> Note {
+    let letter = ORDER_OF_SHARPS[(position + 1).rem_euclid(7) as usize];
+    let accidental = NoteAccidental::from_semitone_offset((position + 1).div_euclid(7)).unwrap();
+
+    Note::new(letter, accidental)
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = match self.mode {
+            KeyMode::Major => "major",
+            KeyMode::Minor => "minor",
+        };
+
+        write!(f, "{} {}", self.tonic.name(), mode)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeyParseError {
+    InvalidFormat,
+    TheoreticalKey,
+}
+
+lazy_static! {
+    static ref KEY_REGEX: Regex =
+        Regex::new(&format!(r"^{NOTE_NAME_PATTERN}\s*(major|minor|m)?$")).unwrap();
+}
+
+// Parses keys like "Eb major", "C# minor" or "C#m". A tonic on its own is a major key.
+impl FromStr for Key {
+    type Err = KeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let captures = KEY_REGEX
+            .captures(s.trim())
+            .ok_or(KeyParseError::InvalidFormat)?;
+
+        let tonic = Note::from_name_captures(&captures, 4);
+        let mode = match captures.get(3).map(|m| m.as_str()) {
+            Some("minor") | Some("m") => KeyMode::Minor,
+            _ => KeyMode::Major,
+        };
+
+        Key::new(tonic, mode).map_err(|_| KeyParseError::TheoreticalKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> Key {
+        Key::from_str(s).unwrap()
+    }
+
+    fn names(notes: &[Note]) -> Vec<String> {
+        notes.iter().map(|note| note.name()).collect()
+    }
+
+    #[test]
+    fn test_signatures() {
+        assert_eq!(key("C major").signature().fifths, 0);
+        assert_eq!(key("A minor").signature().fifths, 0);
+        assert_eq!(key("Gb major").signature().fifths, -6);
+        assert_eq!(key("C#m").signature().fifths, 4);
+        assert_eq!(key("Ab minor").signature().fifths, -7);
+        assert_eq!(key("C# major").signature().fifths, 7);
+
+        assert_eq!(
+            names(&key("Gb major").signature().notes()),
+            ["Bb", "Eb", "Ab", "Db", "Gb", "Cb"]
+        );
+        assert_eq!(
+            names(&key("E major").signature().notes()),
+            ["F#", "C#", "G#", "D#"]
+        );
+        assert!(key("A minor").signature().notes().is_empty());
+
+        assert_eq!(key("Gb major").signature().to_string(), "6 flats");
+        assert_eq!(key("E minor").signature().to_string(), "1 sharp");
+        assert_eq!(key("C major").signature().to_string(), "no sharps or flats");
+    }
+
+    #[test]
+    fn test_theoretical_keys() {
+        assert_eq!(
+            Key::new(Note::from_str("G#").unwrap(), KeyMode::Major),
+            Err(KeyError::TheoreticalKey { fifths: 8 })
+        );
+        assert_eq!(
+            Key::from_fifths(-8, KeyMode::Minor),
+            Err(KeyError::TheoreticalKey { fifths: -8 })
+        );
+        assert_eq!(
+            Key::from_str("Fb major"),
+            Err(KeyParseError::TheoreticalKey)
+        );
+        assert_eq!(Key::from_str("H major"), Err(KeyParseError::InvalidFormat));
+        assert_eq!(
+            key("G# minor").parallel(),
+            Err(KeyError::TheoreticalKey { fifths: 8 })
+        );
+    }
+
+    #[test]
+    fn test_related_keys() {
+        assert_eq!(key("C major").relative(), key("A minor"));
+        assert_eq!(key("Eb minor").relative(), key("Gb major"));
+        assert_eq!(key("C major").parallel(), Ok(key("C minor")));
+        assert_eq!(key("F# minor").parallel(), Ok(key("F# major")));
+        assert_eq!(Key::from_fifths(2, KeyMode::Minor), Ok(key("B minor")));
+        assert_eq!(key("Db").to_string(), "Db major");
+    }
+
+    #[test]
+    fn test_all_keys_follow_their_signatures() {
+        let keys = all_keys();
+        assert_eq!(keys.len(), 30);
+
+        for key in keys {
+            let signature = key.signature();
+            for note in key.notes() {
+                assert_eq!(
+                    note.accidental(),
+                    signature.accidental_for(note.letter()),
+                    "{note} in {key}"
+                );
+                assert_eq!(key.spell(&note), note);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chromatic_spelling() {
+        let spell_all = |key: Key| -> Vec<String> {
+            (0..12)
+                .map(|pitch_class| key.spell_pitch_class(pitch_class).name())
+                .collect()
+        };
+
+        assert_eq!(
+            spell_all(key("C major")),
+            ["C", "C#", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B"]
+        );
+        assert_eq!(
+            spell_all(key("A minor")),
+            ["C", "C#", "D", "Eb", "E", "F", "F#", "G", "G#", "A", "Bb", "B"]
+        );
+        assert_eq!(
+            spell_all(key("F# major")),
+            ["B#", "C#", "D", "D#", "E", "E#", "F#", "G", "G#", "A", "A#", "B"]
+        );
+        assert_eq!(
+            spell_all(key("Eb minor")),
+            ["C", "Db", "D", "Eb", "Fb", "F", "Gb", "G", "Ab", "A", "Bb", "Cb"]
+        );
+
+        let b3 = Note::from_str("B3").unwrap();
+        assert_eq!(key("Gb major").spell(&b3), Note::from_str("Cb4").unwrap());
+    }
+}
@@ -1,3 +1,12 @@
+pub mod chord;
+pub mod harmony;
+pub mod identify;
 pub mod interval;
+pub mod key;
 pub mod note;
+pub mod progression;
+pub mod roman;
 pub mod scale;
+pub mod tension;
+pub mod voice_leading;
+pub mod voicing;
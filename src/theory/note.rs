@@ -1,10 +1,12 @@
-use regex::Regex;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
 use std::str::FromStr;
 
-use crate::theory::interval::Interval;
+use crate::theory::interval::{Interval, IntervalError, OCT1};
 
 use num_derive::FromPrimitive;
 
+use super::chord::ChordQuality;
 use super::scale::Scale;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -23,7 +25,9 @@ pub enum KeyColor {
 }
 
 impl NoteAccidental {
-    fn semitone_offset(&self) -> i8 {
+    // How many semitones the accidental raises the note by, from -2 for a double flat to 2 for a
+    // double sharp.
+    pub fn semitone_offset(&self) -> i8 {
         match self {
             NoteAccidental::None => 0,
             NoteAccidental::Flat => -1,
@@ -32,6 +36,25 @@ impl NoteAccidental {
             NoteAccidental::DoubleSharp => 2,
         }
     }
+
+    // The accidental that raises or lowers a note by `offset` semitones, if there is one.
+    pub(crate) fn from_semitone_offset(offset: i8) -> Option<Self> {
+        match offset {
+            -2 => Some(NoteAccidental::DoubleFlat),
+            -1 => Some(NoteAccidental::Flat),
+            0 => Some(NoteAccidental::None),
+            1 => Some(NoteAccidental::Sharp),
+            2 => Some(NoteAccidental::DoubleSharp),
+            _ => None,
+        }
+    }
+}
+
+// Whether notes that need an accidental should be spelled with sharps or flats.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpellingPreference {
+    Sharps,
+    Flats,
 }
 
 #[derive(Debug)]
@@ -54,15 +77,17 @@ impl FromStr for NoteAccidental {
     }
 }
 
-impl ToString for NoteAccidental {
-    fn to_string(&self) -> String {
-        match self {
-            NoteAccidental::None => "".to_string(),
-            NoteAccidental::Flat => "b".to_string(),
-            NoteAccidental::Sharp => "#".to_string(),
-            NoteAccidental::DoubleFlat => "bb".to_string(),
-            NoteAccidental::DoubleSharp => "##".to_string(),
-        }
+impl std::fmt::Display for NoteAccidental {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let accidental = match self {
+            NoteAccidental::None => "",
+            NoteAccidental::Flat => "b",
+            NoteAccidental::Sharp => "#",
+            NoteAccidental::DoubleFlat => "bb",
+            NoteAccidental::DoubleSharp => "##",
+        };
+
+        write!(f, "{}", accidental)
     }
 }
 
@@ -85,11 +110,13 @@ impl PartialOrd for Note {
     }
 }
 
+// Notes are ordered by pitch. Enharmonic notes are then ordered by letter, so B#3 < C4 < Dbb4,
+// which keeps the ordering consistent with Eq.
 impl Ord for Note {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        return self
-            .inter_octave_semitone_value()
-            .cmp(&other.inter_octave_semitone_value());
+        self.inter_octave_semitone_value()
+            .cmp(&other.inter_octave_semitone_value())
+            .then_with(|| self.diatonic_value().cmp(&other.diatonic_value()))
     }
 }
 
@@ -139,30 +166,47 @@ impl NoteLetter {
     // if that happens, you should delete this project and start over.
     const NUM_NOTE_LETTERS: i8 = 7;
 
-    // Advances the given letter by `number` note letters. Returns the new NoteLetter, as well as
-    // how many octaves the note went up by.
+    pub const ALL: [NoteLetter; 7] = [
+        NoteLetter::C,
+        NoteLetter::D,
+        NoteLetter::E,
+        NoteLetter::F,
+        NoteLetter::G,
+        NoteLetter::A,
+        NoteLetter::B,
+    ];
+
+    // Advances the given letter by `number` note letters, which may be negative to move down.
+    // Returns the new NoteLetter, as well as how many octaves the note moved by (negative if it
+    // crossed below a C).
+    //
+    // This can't fail: the arithmetic is done in i16 so that large numbers don't overflow, and the
+    // euclidean remainder always lands on one of the 7 letters.
     fn advance_by(&self, number: i8) -> (Self, i8) {
-        let new_letter_num = (*self as i8) + number;
-        let new_octaves = new_letter_num / NoteLetter::NUM_NOTE_LETTERS;
+        let num_note_letters = NoteLetter::NUM_NOTE_LETTERS as i16;
+        let new_letter_num = (*self as i16) + number as i16;
+        let new_octaves = new_letter_num.div_euclid(num_note_letters) as i8;
 
-        match num::FromPrimitive::from_i8(new_letter_num % NoteLetter::NUM_NOTE_LETTERS) {
-            Some(note_letter) => (note_letter, new_octaves),
-            None => panic!("Always should be able to advance a NoteLetter into another"),
-        }
+        let note_letter =
+            num::FromPrimitive::from_i16(new_letter_num.rem_euclid(num_note_letters)).unwrap();
+
+        (note_letter, new_octaves)
     }
 }
 
-impl ToString for NoteLetter {
-    fn to_string(&self) -> String {
-        match self {
-            NoteLetter::A => "A".to_string(),
-            NoteLetter::B => "B".to_string(),
-            NoteLetter::C => "C".to_string(),
-            NoteLetter::D => "D".to_string(),
-            NoteLetter::E => "E".to_string(),
-            NoteLetter::F => "F".to_string(),
-            NoteLetter::G => "G".to_string(),
-        }
+impl std::fmt::Display for NoteLetter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let letter = match self {
+            NoteLetter::A => "A",
+            NoteLetter::B => "B",
+            NoteLetter::C => "C",
+            NoteLetter::D => "D",
+            NoteLetter::E => "E",
+            NoteLetter::F => "F",
+            NoteLetter::G => "G",
+        };
+
+        write!(f, "{}", letter)
     }
 }
 
@@ -199,14 +243,104 @@ impl Note {
     }
 
     pub fn ascending_scale(&self, scale: &Scale) -> Vec<Note> {
-        let mut scale_notes = Vec::<Note>::with_capacity(scale.ascending.len());
+        self.try_ascending_scale(scale)
+            .unwrap_or_else(|err| panic!("Couldn't spell scale from {self}: {err:?}"))
+    }
+
+    // Like ascending_scale, but returns an error instead of panicking if any scale note would need
+    // more than a double accidental.
+    pub fn try_ascending_scale(&self, scale: &Scale) -> Result<Vec<Note>, ApplyIntervalError> {
+        scale
+            .ascending
+            .iter()
+            .map(|interval| self.try_apply_interval(interval))
+            .collect()
+    }
 
-        for interval in scale.ascending.iter() {
-            let scale_note = self.apply_interval(interval);
-            scale_notes.push(scale_note)
+    // Returns the scale from the octave above self back down to self, so it has one more note than
+    // ascending_scale. If the scale has no separate descending form, this is the ascending scale
+    // reversed.
+    pub fn descending_scale(&self, scale: &Scale) -> Vec<Note> {
+        self.try_descending_scale(scale)
+            .unwrap_or_else(|err| panic!("Couldn't spell scale from {self}: {err:?}"))
+    }
+
+    // Like descending_scale, but returns an error instead of panicking if any scale note would
+    // need more than a double accidental.
+    pub fn try_descending_scale(&self, scale: &Scale) -> Result<Vec<Note>, ApplyIntervalError> {
+        let mut scale_notes = Vec::<Note>::with_capacity(scale.ascending.len() + 1);
+        scale_notes.push(self.try_apply_interval(&OCT1)?);
+
+        match &scale.descending {
+            Some(descending) => {
+                for interval in descending.iter() {
+                    scale_notes.push(self.try_apply_interval(interval)?);
+                }
+            }
+            None => {
+                for interval in scale.ascending.iter().rev() {
+                    scale_notes.push(self.try_apply_interval(interval)?);
+                }
+            }
         }
 
-        scale_notes
+        Ok(scale_notes)
+    }
+
+    // Walks the scale up from self to the octave and back down again, ending on self.
+    pub fn up_and_back(&self, scale: &Scale) -> impl Iterator<Item = Note> {
+        self.try_up_and_back(scale)
+            .unwrap_or_else(|err| panic!("Couldn't spell scale from {self}: {err:?}"))
+    }
+
+    // Like up_and_back, but returns an error instead of panicking if any scale note would need
+    // more than a double accidental.
+    pub fn try_up_and_back(
+        &self,
+        scale: &Scale,
+    ) -> Result<impl Iterator<Item = Note>, ApplyIntervalError> {
+        let ascending = self.try_ascending_scale(scale)?;
+        let descending = self.try_descending_scale(scale)?;
+        Ok(ascending.into_iter().chain(descending))
+    }
+
+    // Spells the chord of the given quality with self as the root. Chord tones are spelled by
+    // letter, so a minor 7th on Db is Db-Fb-Ab-Cb, not Db-E-Ab-B.
+    pub fn chord(&self, quality: &ChordQuality) -> Vec<Note> {
+        self.try_chord(quality)
+            .unwrap_or_else(|err| panic!("Couldn't spell chord on {self}: {err:?}"))
+    }
+
+    // Like chord, but returns an error instead of panicking if any chord tone would need more than
+    // a double accidental.
+    pub fn try_chord(&self, quality: &ChordQuality) -> Result<Vec<Note>, ApplyIntervalError> {
+        quality
+            .intervals
+            .iter()
+            .map(|interval| self.try_apply_interval(interval))
+            .collect()
+    }
+
+    pub fn letter(&self) -> NoteLetter {
+        self.letter
+    }
+
+    pub fn accidental(&self) -> NoteAccidental {
+        self.accidental
+    }
+
+    pub fn octave(&self) -> i8 {
+        self.octave
+    }
+
+    // The same letter and accidental in another octave.
+    pub fn with_octave(&self, octave: i8) -> Note {
+        Note { octave, ..*self }
+    }
+
+    // The letter and accidental of the note, without its octave, like "Bb".
+    pub fn name(&self) -> String {
+        format!("{}{}", self.letter, self.accidental)
     }
 
     pub fn key_color(&self) -> KeyColor {
@@ -216,56 +350,258 @@ impl Note {
             0 | 2 | 4 | 5 | 7 | 9 | 11 => KeyColor::White,
             1 | 3 | 6 | 8 | 10 => KeyColor::Black,
             _ => panic!(
-                "Octaveless semitone offset should never fall outside of [1, 12), got {}",
+                "Octaveless semitone offset should never fall outside of [0, 12), got {}",
                 intra_octave_semitone_value
             ),
         }
     }
 
-    pub fn inter_octave_semitone_value(&self) -> i8 {
-        let octave_offset = 12 * self.octave;
-        // We do *not* apply % 12 to any part of this, because we want to overflow into the next
-        // octave if that's what the accidentals do.
-        return octave_offset + self.letter.semitone_offset() + self.accidental.semitone_offset();
+    // The number of semitones between C0 and this note. We do *not* wrap the accidental into the
+    // letter's octave, because we want Cb4 to land in the 3rd octave and B#3 in the 4th, which is
+    // what the accidentals do. This is an i16 since 12 * octave doesn't fit in an i8.
+    pub fn inter_octave_semitone_value(&self) -> i16 {
+        let octave_offset = 12 * self.octave as i16;
+        octave_offset + (self.letter.semitone_offset() + self.accidental.semitone_offset()) as i16
     }
 
+    // The pitch class of the note, from 0 (C) to 11 (B). Cb is 11 and B# is 0.
     pub fn intra_octave_semitone_value(&self) -> i8 {
-        return (self.letter.semitone_offset() + self.accidental.semitone_offset()) % 12;
+        (self.letter.semitone_offset() + self.accidental.semitone_offset()).rem_euclid(12)
     }
 
-    // ApplyInterval moves self by the given interval, and returns the resulting note.
+    // ApplyInterval moves self up by the given interval, and returns the resulting note. Panics if
+    // the note would need more than a double accidental; use try_apply_interval when iterating over
+    // roots and intervals that might produce one.
     pub fn apply_interval(&self, interval: &Interval) -> Self {
+        self.try_apply_interval(interval)
+            .unwrap_or_else(|err| panic!("Couldn't apply {interval} to {self}: {err:?}"))
+    }
+
+    // Like apply_interval, but returns an error instead of panicking.
+    pub fn try_apply_interval(&self, interval: &Interval) -> Result<Self, ApplyIntervalError> {
+        self.try_move_by(interval.number, interval.semitones)
+    }
+
+    // Moves self down by the given interval, and returns the resulting note. Like going up, the
+    // letter is found first, so a major 3rd below Ab is Fb, not E.
+    pub fn apply_interval_below(&self, interval: &Interval) -> Self {
+        self.try_apply_interval_below(interval)
+            .unwrap_or_else(|err| panic!("Couldn't apply {interval} below {self}: {err:?}"))
+    }
+
+    // Like apply_interval_below, but returns an error instead of panicking.
+    pub fn try_apply_interval_below(
+        &self,
+        interval: &Interval,
+    ) -> Result<Self, ApplyIntervalError> {
+        self.try_move_by(-interval.number, -interval.semitones)
+    }
+
+    // Moves self by `letters` note letters and `semitones` semitones, both of which are negative
+    // when moving down.
+    fn try_move_by(&self, letters: i8, semitones: i8) -> Result<Self, ApplyIntervalError> {
         let our_letter = self.letter;
 
-        // The NoteLetter corresponding to the returned note is interval.number letters away from
+        // The NoteLetter corresponding to the returned note is `letters` letters away from
         // our_letter.
-        let (new_note_letter, new_octaves) = our_letter.advance_by(interval.number);
+        let (new_note_letter, new_octaves) = our_letter.advance_by(letters);
 
         let new_note = &mut Note {
             letter: new_note_letter,
             accidental: NoteAccidental::None,
-            octave: self.octave + new_octaves,
+            octave: self
+                .octave
+                .checked_add(new_octaves)
+                .ok_or(ApplyIntervalError::OctaveOutOfRange)?,
         };
 
-        let distance = interval.semitones - new_note.get_semitone_distance(self);
-        match distance {
-            -2 => new_note.accidental = NoteAccidental::DoubleFlat,
-            -1 => new_note.accidental = NoteAccidental::Flat,
-            0 => (),
-            1 => new_note.accidental = NoteAccidental::Sharp,
-            2 => new_note.accidental = NoteAccidental::DoubleSharp,
-            i => panic!(
-                "All new notes should be within a double flat/sharp of their intended interval, received {i}"
-            ),
+        // Distances that don't fit in an i8 are clamped, which still reports too many accidentals.
+        let distance = semitones as i16 - new_note.get_semitone_distance(self);
+        let distance = distance.clamp(i8::MIN as i16, i8::MAX as i16) as i8;
+        new_note.accidental = NoteAccidental::from_semitone_offset(distance).ok_or(
+            ApplyIntervalError::TooManyAccidentals {
+                letter: new_note_letter,
+                offset: distance,
+            },
+        )?;
+
+        Ok(*new_note)
+    }
+
+    // The MIDI note number of this pitch, where middle C (C4) is 60 and A4 is 69. Returns None for
+    // notes outside of MIDI's 0 (C-1) to 127 (G9) range.
+    pub fn to_midi(&self) -> Option<u8> {
+        u8::try_from(self.inter_octave_semitone_value() + 12)
+            .ok()
+            .filter(|midi| *midi <= 127)
+    }
+
+    // The note with the given MIDI note number. Black keys are spelled with sharps or flats
+    // according to the preference, so 61 is C#4 or Db4.
+    pub fn from_midi(midi: u8, preference: SpellingPreference) -> Note {
+        let (letter, accidental) = match midi % 12 {
+            0 => (NoteLetter::C, NoteAccidental::None),
+            1 => (NoteLetter::C, NoteAccidental::Sharp),
+            2 => (NoteLetter::D, NoteAccidental::None),
+            3 => (NoteLetter::D, NoteAccidental::Sharp),
+            4 => (NoteLetter::E, NoteAccidental::None),
+            5 => (NoteLetter::F, NoteAccidental::None),
+            6 => (NoteLetter::F, NoteAccidental::Sharp),
+            7 => (NoteLetter::G, NoteAccidental::None),
+            8 => (NoteLetter::G, NoteAccidental::Sharp),
+            9 => (NoteLetter::A, NoteAccidental::None),
+            10 => (NoteLetter::A, NoteAccidental::Sharp),
+            _ => (NoteLetter::B, NoteAccidental::None),
+        };
+
+        let note = Note {
+            letter,
+            accidental,
+            octave: (midi / 12) as i8 - 1,
+        };
+        note.spell_with(preference)
+    }
+
+    // The frequency of this note in hertz under 12-tone equal temperament, with A4 tuned to
+    // `a4_hz` (usually STANDARD_A4_HZ). Enharmonic notes have the same frequency.
+    pub fn frequency(&self, a4_hz: f64) -> f64 {
+        let a4 = Note {
+            letter: NoteLetter::A,
+            accidental: NoteAccidental::None,
+            octave: 4,
+        };
+        let semitones_from_a4 = self.get_semitone_distance(&a4);
+
+        a4_hz * 2f64.powf(semitones_from_a4 as f64 / 12.0)
+    }
+
+    // Whether the two notes are the same key on the piano, regardless of spelling. Octaves matter,
+    // so Cb4 is enharmonic to B3 but not to B4.
+    pub fn is_enharmonic_to(&self, other: &Note) -> bool {
+        self.inter_octave_semitone_value() == other.inter_octave_semitone_value()
+    }
+
+    // Spells the same pitch with the given letter, adjusting the octave if the letter is on the
+    // other side of a C: C4 respelled with B is B#3. Fails if the letter is more than a double
+    // accidental away.
+    pub fn respell(&self, letter: NoteLetter) -> Result<Note, RespellError> {
+        let pitch = self.inter_octave_semitone_value();
+
+        for octave_offset in [-1, 0, 1] {
+            let octave = self.octave.saturating_add(octave_offset);
+            let natural = Note {
+                letter,
+                accidental: NoteAccidental::None,
+                octave,
+            };
+
+            let offset = pitch - natural.inter_octave_semitone_value();
+            let accidental = i8::try_from(offset)
+                .ok()
+                .and_then(NoteAccidental::from_semitone_offset);
+            if let Some(accidental) = accidental {
+                return Ok(Note {
+                    letter,
+                    accidental,
+                    octave,
+                });
+            }
+        }
+
+        Err(RespellError::LetterTooFar)
+    }
+
+    // Spells the same pitch without an accidental if possible, and otherwise with a single sharp or
+    // flat according to the preference. Bb4 with Sharps is A#4, and E#4 with either is F4. Panics
+    // if that spelling's octave doesn't fit in an i8, which only happens for notes like B#127.
+    pub fn spell_with(&self, preference: SpellingPreference) -> Note {
+        let spellings: Vec<Note> = NoteLetter::ALL
+            .iter()
+            .filter_map(|letter| self.respell(*letter).ok())
+            .collect();
+
+        let preferred_accidental = match preference {
+            SpellingPreference::Sharps => NoteAccidental::Sharp,
+            SpellingPreference::Flats => NoteAccidental::Flat,
+        };
+
+        // Every pitch has either a natural spelling, or both a sharp and a flat one.
+        *spellings
+            .iter()
+            .find(|note| note.accidental == NoteAccidental::None)
+            .or_else(|| {
+                spellings
+                    .iter()
+                    .find(|note| note.accidental == preferred_accidental)
+            })
+            .unwrap()
+    }
+
+    // The least surprising spelling of the same pitch: naturals stay put, Fb becomes E, B#3
+    // becomes C4, and double accidentals collapse to a single one in the same direction, so E##
+    // becomes F# and Abb becomes G.
+    pub fn simplest_spelling(&self) -> Note {
+        match self.accidental {
+            NoteAccidental::Flat | NoteAccidental::DoubleFlat => {
+                self.spell_with(SpellingPreference::Flats)
+            }
+            _ => self.spell_with(SpellingPreference::Sharps),
+        }
+    }
+
+    // Returns the interval from self up to other, spelled by letter: Db4 to Fb4 is a minor 3rd,
+    // while Db4 to E4 is an augmented 2nd.
+    pub fn interval_to(&self, other: &Note) -> Result<Interval, IntervalError> {
+        let number = other.diatonic_value() - self.diatonic_value();
+        let semitones = other.get_semitone_distance(self);
+        if number < 0 {
+            return Err(IntervalError::Descending);
+        }
+
+        match (i8::try_from(number), i8::try_from(semitones)) {
+            (Ok(number), Ok(semitones)) => Interval::from_number_and_semitones(number, semitones),
+            _ => Err(IntervalError::TooWide),
         }
+    }
 
-        *new_note
+    // The number of note letters between C0 and this note, ignoring accidentals.
+    fn diatonic_value(&self) -> i16 {
+        NoteLetter::NUM_NOTE_LETTERS as i16 * self.octave as i16 + self.letter as i16
     }
 
-    fn get_semitone_distance(&self, other: &Self) -> i8 {
+    fn get_semitone_distance(&self, other: &Self) -> i16 {
         let our_value = self.inter_octave_semitone_value();
         let other_value = other.inter_octave_semitone_value();
-        return our_value - other_value;
+        our_value - other_value
+    }
+}
+
+// Concert pitch: the A above middle C is tuned to 440 Hz.
+pub const STANDARD_A4_HZ: f64 = 440.0;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ApplyIntervalError {
+    // The resulting note would need `offset` semitones of accidentals on `letter`, which is more
+    // than a double flat or sharp. For example, an augmented 2nd above B# is C with a triple
+    // sharp.
+    TooManyAccidentals { letter: NoteLetter, offset: i8 },
+    // The resulting note's octave doesn't fit in an i8.
+    OctaveOutOfRange,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RespellError {
+    // The requested letter is more than a double sharp or flat away from the note's pitch.
+    LetterTooFar,
+}
+
+// Subtracting a lower note from a higher one gives the interval between them, so Fb4 - Db4 is a
+// minor 3rd.
+impl std::ops::Sub for Note {
+    type Output = Result<Interval, IntervalError>;
+
+    fn sub(self, lower: Note) -> Self::Output {
+        lower.interval_to(&self)
     }
 }
 
@@ -274,29 +610,51 @@ pub enum NoteParseError {
     InvalidFormat,
 }
 
+// Matches a note letter and its optional accidental, like "Bb" or "F##", without an octave. The
+// longer accidentals come first so that unanchored uses (like the root of a chord symbol) don't
+// stop after the first flat or sharp.
+pub(crate) const NOTE_NAME_PATTERN: &str = r"([A-G])(bb|b|♭♭|♭|##|#|♯♯|♯|×)?";
+
+lazy_static! {
+    static ref NOTE_REGEX: Regex =
+        Regex::new(&format!("^{NOTE_NAME_PATTERN}(-?[0-9]+)?$")).unwrap();
+}
+
+impl Note {
+    // Builds a note from the letter and accidental captured by NOTE_NAME_PATTERN, which must be
+    // capture groups 1 and 2 of the given captures.
+    pub(crate) fn from_name_captures(captures: &Captures, octave: i8) -> Note {
+        let letter = NoteLetter::from_str(captures.get(1).unwrap().as_str()).unwrap();
+        let accidental = captures.get(2).map_or(NoteAccidental::None, |m| {
+            NoteAccidental::from_str(m.as_str()).unwrap()
+        });
+
+        Note {
+            letter,
+            accidental,
+            octave,
+        }
+    }
+}
+
 impl FromStr for Note {
     type Err = NoteParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new(r"^([A-G])(b|bb|♭|♭♭|#|##|♯|♯♯|×)?([0-8])?$").unwrap();
-
-        match re.captures(s) {
+        match NOTE_REGEX.captures(s) {
             // TODO(neil): Consider giving more ergonomic error messages
             None => Err(NoteParseError::InvalidFormat),
             Some(captures) => {
-                let letter = NoteLetter::from_str(captures.get(1).unwrap().as_str()).unwrap();
-                let accidental = captures.get(2).map_or(NoteAccidental::None, |m| {
-                    NoteAccidental::from_str(m.as_str()).unwrap()
-                });
-                let octave = captures
-                    .get(3)
-                    .map_or(4, |m| m.as_str().parse::<i8>().unwrap());
-
-                Ok(Note {
-                    letter,
-                    accidental,
-                    octave,
-                })
+                // Any octave that fits in an i8 is allowed, including negative ones like C-1.
+                let octave = match captures.get(3) {
+                    None => 4,
+                    Some(m) => m
+                        .as_str()
+                        .parse::<i8>()
+                        .map_err(|_| NoteParseError::InvalidFormat)?,
+                };
+
+                Ok(Note::from_name_captures(&captures, octave))
             }
         }
     }
@@ -304,7 +662,13 @@ impl FromStr for Note {
 
 #[cfg(test)]
 mod tests {
-    use crate::theory::interval::{MAJ2, MAJ3, MIN3, MIN6, MIN7, OCT1, PERF1, PERF4, PERF5};
+    use crate::theory::chord::DIMINISHED_SEVENTH;
+    use crate::theory::interval::AUG2;
+    use crate::theory::interval::{
+        IntervalQuality, MAJ2, MAJ3, MIN2, MIN3, MIN6, MIN7, OCT1, PERF1, PERF4, PERF5, TRITONE4,
+        TRITONE5,
+    };
+    use crate::theory::scale::{ALL_SCALES, CLASSICAL_MELODIC_MINOR, IONIAN, LOCRIAN, MIXO};
 
     use super::*;
 
@@ -341,6 +705,378 @@ mod tests {
         assert_eq!(f.apply_interval(&OCT1).to_string(), "F5");
     }
 
+    #[test]
+    fn test_descending_scales() {
+        let c = Note::new(NoteLetter::C, NoteAccidental::None);
+
+        let mixo: Vec<String> = c
+            .descending_scale(&MIXO)
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(mixo, ["C5", "Bb4", "A4", "G4", "F4", "E4", "D4", "C4"]);
+
+        let melodic: Vec<String> = c
+            .up_and_back(&CLASSICAL_MELODIC_MINOR)
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(
+            melodic,
+            [
+                "C4", "D4", "Eb4", "F4", "G4", "A4", "B4", "C5", "Bb4", "Ab4", "G4", "F4", "Eb4",
+                "D4", "C4"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_intervals_below() {
+        let below = |note: &str, interval: &Interval| {
+            Note::from_str(note)
+                .unwrap()
+                .apply_interval_below(interval)
+                .to_string()
+        };
+
+        assert_eq!(below("Ab4", &MAJ3), "Fb4");
+        assert_eq!(below("C4", &MIN3), "A3");
+        assert_eq!(below("C4", &MAJ2), "Bb3");
+        assert_eq!(below("D4", &PERF5), "G3");
+        assert_eq!(below("Fb4", &MIN2), "Eb4");
+        assert_eq!(below("F#5", &OCT1), "F#4");
+        assert_eq!(below("E4", &TRITONE5), "A#3");
+    }
+
+    #[test]
+    fn test_try_apply_interval() {
+        let bs = Note::from_str("B#").unwrap();
+        assert_eq!(
+            bs.try_apply_interval(&AUG2),
+            Err(ApplyIntervalError::TooManyAccidentals {
+                letter: NoteLetter::C,
+                offset: 3
+            })
+        );
+        assert_eq!(bs.try_apply_interval(&MIN2).unwrap().to_string(), "C#5");
+
+        let fbb = Note::from_str("Fbb").unwrap();
+        assert!(fbb.try_apply_interval_below(&AUG2).is_err());
+        assert!(fbb.try_chord(&DIMINISHED_SEVENTH).is_err());
+    }
+
+    #[test]
+    fn test_try_scales_never_panic() {
+        // Every root with up to a double accidental, against every scale in the catalog. Some of
+        // these can't be spelled, but none of them should panic, and the ones that can be spelled
+        // should match the panicking versions.
+        let mut unspellable = 0;
+
+        for letter in ["C", "D", "E", "F", "G", "A", "B"] {
+            for accidental in ["bb", "b", "", "#", "##"] {
+                let root = Note::from_str(&format!("{letter}{accidental}")).unwrap();
+
+                for scale in ALL_SCALES.iter() {
+                    match root.try_ascending_scale(scale) {
+                        Ok(notes) => assert_eq!(notes, root.ascending_scale(scale)),
+                        Err(_) => unspellable += 1,
+                    }
+                    match root.try_descending_scale(scale) {
+                        Ok(notes) => assert_eq!(notes, root.descending_scale(scale)),
+                        Err(_) => unspellable += 1,
+                    }
+                    match root.try_up_and_back(scale) {
+                        Ok(notes) => assert!(notes.eq(root.up_and_back(scale))),
+                        Err(_) => assert!(
+                            root.try_ascending_scale(scale).is_err()
+                                || root.try_descending_scale(scale).is_err()
+                        ),
+                    }
+                }
+            }
+        }
+        assert!(unspellable > 0);
+
+        let c = Note::from_str("C").unwrap();
+        assert!(ALL_SCALES
+            .iter()
+            .all(|scale| c.try_ascending_scale(scale).is_ok()));
+
+        // The 2nd of B## major would be C with a triple sharp.
+        let bss = Note::from_str("B##").unwrap();
+        let c_triple_sharp = ApplyIntervalError::TooManyAccidentals {
+            letter: NoteLetter::C,
+            offset: 3,
+        };
+        assert_eq!(bss.try_ascending_scale(&IONIAN), Err(c_triple_sharp));
+        assert!(bss.try_up_and_back(&IONIAN).is_err());
+
+        // Going down Fbb locrian, the b7 comes first, and would be E with a triple flat.
+        let fbb = Note::from_str("Fbb").unwrap();
+        assert_eq!(
+            fbb.try_descending_scale(&LOCRIAN),
+            Err(ApplyIntervalError::TooManyAccidentals {
+                letter: NoteLetter::E,
+                offset: -3
+            })
+        );
+    }
+
+    #[test]
+    fn test_enharmonics() {
+        let note = |s: &str| Note::from_str(s).unwrap();
+
+        assert!(note("Cb4").is_enharmonic_to(&note("B3")));
+        assert!(note("E#4").is_enharmonic_to(&note("F4")));
+        assert!(note("Gbb4").is_enharmonic_to(&note("F4")));
+        assert!(!note("C4").is_enharmonic_to(&note("C5")));
+        assert!(!note("Cb4").is_enharmonic_to(&note("B4")));
+
+        assert_eq!(note("C#4").respell(NoteLetter::D), Ok(note("Db4")));
+        assert_eq!(note("C4").respell(NoteLetter::B), Ok(note("B#3")));
+        assert_eq!(note("B3").respell(NoteLetter::C), Ok(note("Cb4")));
+        assert_eq!(
+            note("E4").respell(NoteLetter::G),
+            Err(RespellError::LetterTooFar)
+        );
+        assert_eq!(
+            note("C4").respell(NoteLetter::F),
+            Err(RespellError::LetterTooFar)
+        );
+
+        assert_eq!(note("Fb4").simplest_spelling(), note("E4"));
+        assert_eq!(note("B#3").simplest_spelling(), note("C4"));
+        assert_eq!(note("Cb4").simplest_spelling(), note("B3"));
+        assert_eq!(note("E##4").simplest_spelling(), note("F#4"));
+        assert_eq!(note("Abb4").simplest_spelling(), note("G4"));
+        assert_eq!(note("Db4").simplest_spelling(), note("Db4"));
+        assert_eq!(note("A#4").simplest_spelling(), note("A#4"));
+
+        assert_eq!(
+            note("A#4").spell_with(SpellingPreference::Flats),
+            note("Bb4")
+        );
+        assert_eq!(
+            note("Gb4").spell_with(SpellingPreference::Sharps),
+            note("F#4")
+        );
+        assert_eq!(
+            note("E#4").spell_with(SpellingPreference::Flats),
+            note("F4")
+        );
+    }
+
+    #[test]
+    fn test_interval_between_notes() {
+        let interval = |low: &str, high: &str| {
+            Note::from_str(low)
+                .unwrap()
+                .interval_to(&Note::from_str(high).unwrap())
+        };
+
+        assert_eq!(interval("Db4", "Fb4"), Ok(MIN3));
+        assert_eq!(interval("C4", "F#4"), Ok(TRITONE4));
+        assert_eq!(interval("C4", "Gb4"), Ok(TRITONE5));
+        assert_eq!(interval("B3", "C4"), Ok(MIN2));
+        assert_eq!(interval("F4", "F5"), Ok(OCT1));
+        assert_eq!(
+            interval("E4", "Fb4").unwrap().quality,
+            IntervalQuality::Diminished
+        );
+        assert_eq!(
+            interval("Cb4", "G#4").unwrap().quality,
+            IntervalQuality::DoublyAugmented
+        );
+        assert_eq!(
+            interval("C#4", "Ebb4").unwrap().quality,
+            IntervalQuality::DoublyDiminished
+        );
+
+        let ninth = interval("C4", "D5").unwrap();
+        assert_eq!((ninth.number, ninth.semitones), (8, 14));
+
+        assert_eq!(
+            Note::from_str("Fb4").unwrap() - Note::from_str("Db4").unwrap(),
+            Ok(MIN3)
+        );
+    }
+
+    #[test]
+    fn test_unrepresentable_intervals() {
+        let interval = |low: &str, high: &str| {
+            Note::from_str(low)
+                .unwrap()
+                .interval_to(&Note::from_str(high).unwrap())
+        };
+
+        assert_eq!(interval("D4", "C4"), Err(IntervalError::Descending));
+        assert_eq!(
+            interval("Cbb4", "D##4"),
+            Err(IntervalError::UnnamedQuality {
+                number: 1,
+                semitones: 6
+            })
+        );
+    }
+
+    const ALL_ACCIDENTALS: [NoteAccidental; 5] = [
+        NoteAccidental::DoubleFlat,
+        NoteAccidental::Flat,
+        NoteAccidental::None,
+        NoteAccidental::Sharp,
+        NoteAccidental::DoubleSharp,
+    ];
+
+    // Every one of the 35 spellings, in the given octaves.
+    fn all_spellings(octaves: &[i8]) -> Vec<Note> {
+        let mut notes = vec![];
+        for octave in octaves {
+            for letter in NoteLetter::ALL {
+                for accidental in ALL_ACCIDENTALS {
+                    notes.push(Note {
+                        letter,
+                        accidental,
+                        octave: *octave,
+                    });
+                }
+            }
+        }
+        notes
+    }
+
+    #[test]
+    fn test_all_spellings_round_trip() {
+        for note in all_spellings(&[i8::MIN, i8::MAX]) {
+            assert_eq!(Note::from_str(&note.to_string()), Ok(note));
+            assert!((0..12).contains(&note.intra_octave_semitone_value()));
+            note.key_color();
+        }
+
+        for note in all_spellings(&[-1, 0, 4, 9]) {
+            assert_eq!(Note::from_str(&note.to_string()), Ok(note));
+
+            let intra = note.intra_octave_semitone_value();
+            assert!((0..12).contains(&intra), "{note} has pitch class {intra}");
+            assert_eq!(
+                note.inter_octave_semitone_value().rem_euclid(12),
+                intra as i16
+            );
+
+            // key_color must not panic, and agrees with the simplest spelling of the same pitch.
+            let simplest = note.simplest_spelling();
+            assert!(simplest.is_enharmonic_to(&note));
+            assert_eq!(note.key_color(), simplest.key_color());
+            assert_eq!(
+                note.key_color() == KeyColor::White,
+                simplest.accidental == NoteAccidental::None
+            );
+        }
+    }
+
+    #[test]
+    fn test_octave_boundaries() {
+        let note = |s: &str| Note::from_str(s).unwrap();
+
+        assert_eq!(note("Cb4").intra_octave_semitone_value(), 11);
+        assert_eq!(note("B#3").intra_octave_semitone_value(), 0);
+        assert_eq!(note("Dbb4").intra_octave_semitone_value(), 0);
+        assert_eq!(note("B##3").intra_octave_semitone_value(), 1);
+        assert_eq!(note("Cb4").key_color(), KeyColor::White);
+        assert_eq!(note("B#3").key_color(), KeyColor::White);
+        assert_eq!(note("Fbb4").key_color(), KeyColor::Black);
+
+        assert_eq!(
+            note("Cb4").inter_octave_semitone_value(),
+            note("B3").inter_octave_semitone_value()
+        );
+        assert_eq!(
+            note("B#3").inter_octave_semitone_value(),
+            note("C4").inter_octave_semitone_value()
+        );
+        assert_eq!(note("C-1").inter_octave_semitone_value(), -12);
+        assert_eq!(note("B#-1").apply_interval(&MIN2), note("C#0"));
+        assert_eq!(note("C0").apply_interval_below(&MIN2), note("B-1"));
+        assert_eq!(note("Cb0").apply_interval(&OCT1), note("Cb1"));
+
+        assert_eq!(note("C127").inter_octave_semitone_value(), 1524);
+        assert_eq!(Note::from_str("C128"), Err(NoteParseError::InvalidFormat));
+        assert_eq!(Note::from_str("C-129"), Err(NoteParseError::InvalidFormat));
+        assert_eq!(
+            note("B127").try_apply_interval(&MIN2),
+            Err(ApplyIntervalError::OctaveOutOfRange)
+        );
+        assert_eq!(
+            note("C-128").interval_to(&note("C127")),
+            Err(IntervalError::TooWide)
+        );
+        assert_eq!(
+            note("Cb4").interval_to(&note("B#4")).unwrap().quality,
+            IntervalQuality::DoublyAugmented
+        );
+    }
+
+    #[test]
+    fn test_midi() {
+        let note = |s: &str| Note::from_str(s).unwrap();
+
+        assert_eq!(note("C4").to_midi(), Some(60));
+        assert_eq!(note("A4").to_midi(), Some(69));
+        assert_eq!(note("B#3").to_midi(), Some(60));
+        assert_eq!(note("Cb4").to_midi(), Some(59));
+        assert_eq!(note("C-1").to_midi(), Some(0));
+        assert_eq!(note("G9").to_midi(), Some(127));
+        assert_eq!(note("Cb-1").to_midi(), None);
+        assert_eq!(note("G#9").to_midi(), None);
+
+        assert_eq!(Note::from_midi(60, SpellingPreference::Sharps), note("C4"));
+        assert_eq!(Note::from_midi(61, SpellingPreference::Sharps), note("C#4"));
+        assert_eq!(Note::from_midi(61, SpellingPreference::Flats), note("Db4"));
+        assert_eq!(Note::from_midi(0, SpellingPreference::Flats), note("C-1"));
+        assert_eq!(Note::from_midi(127, SpellingPreference::Flats), note("G9"));
+
+        for midi in 0..=127 {
+            for preference in [SpellingPreference::Sharps, SpellingPreference::Flats] {
+                assert_eq!(Note::from_midi(midi, preference).to_midi(), Some(midi));
+            }
+        }
+    }
+
+    #[test]
+    fn test_frequency() {
+        let note = |s: &str| Note::from_str(s).unwrap();
+        let close = |a: f64, b: f64| (a - b).abs() < 0.01;
+
+        assert_eq!(note("A4").frequency(STANDARD_A4_HZ), 440.0);
+        assert_eq!(note("A5").frequency(STANDARD_A4_HZ), 880.0);
+        assert_eq!(note("A3").frequency(STANDARD_A4_HZ), 220.0);
+        assert!(close(note("C4").frequency(STANDARD_A4_HZ), 261.63));
+        assert!(close(note("C-1").frequency(STANDARD_A4_HZ), 8.18));
+        assert!(close(note("A4").frequency(442.0), 442.0));
+        assert!(close(note("Bb3").frequency(415.0), 219.84));
+        assert_eq!(
+            note("B#3").frequency(STANDARD_A4_HZ),
+            note("C4").frequency(STANDARD_A4_HZ)
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let note = |s: &str| Note::from_str(s).unwrap();
+
+        assert!(note("Cb4") < note("C4"));
+        assert!(note("B3") < note("Cb4"));
+        assert!(note("B#3") < note("C4"));
+        assert!(note("C4") < note("Dbb4"));
+        assert!(note("B#-1") > note("B-1"));
+        assert!(note("C-1") < note("C0"));
+
+        let mut notes = all_spellings(&[-1, 0, 3, 4]);
+        notes.sort();
+        for pair in notes.windows(2) {
+            assert!(pair[0] < pair[1], "{} should be below {}", pair[0], pair[1]);
+            assert!(pair[0].inter_octave_semitone_value() <= pair[1].inter_octave_semitone_value());
+        }
+    }
+
     #[test]
     fn str_to_note() {
         // Note, no accidentals
@@ -0,0 +1,386 @@
+use lazy_static::lazy_static;
+
+use crate::theory::chord::*;
+use crate::theory::interval::*;
+use crate::theory::key::{Key, KeyMode};
+use crate::theory::note::{ApplyIntervalError, Note};
+
+// Every progression is counted in 4/4.
+const BEATS_PER_BAR: u8 = 4;
+
+// One chord of a progression, written relative to the progression's root so that the whole
+// progression can be played from any root.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ProgressionStep {
+    // From the root of the progression up to the root of this chord.
+    pub interval: Interval,
+    pub quality: &'static ChordQuality,
+
+    // Tensions that are part of the sound of the progression, like the b9 on the V7 of a minor
+    // ii-V-i.
+    pub tensions: Vec<Interval>,
+
+    // How many beats the chord lasts.
+    pub beats: u8,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct Progression {
+    pub name: &'static str,
+
+    // Whether the progression is in a major or minor key, which decides which keys we drill it in.
+    pub mode: KeyMode,
+
+    pub steps: Vec<ProgressionStep>,
+}
+
+fn step(interval: Interval, quality: &'static ChordQuality, beats: u8) -> ProgressionStep {
+    ProgressionStep {
+        interval,
+        quality,
+        tensions: vec![],
+        beats,
+    }
+}
+
+fn step_with_tensions(
+    interval: Interval,
+    quality: &'static ChordQuality,
+    tensions: Vec<Interval>,
+    beats: u8,
+) -> ProgressionStep {
+    ProgressionStep {
+        interval,
+        quality,
+        tensions,
+        beats,
+    }
+}
+
+lazy_static! {
+    // ii-V-Is
+
+    pub static ref MAJOR_II_V_I: Progression = Progression {
+        name: "major ii-V-I",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(MAJ2, &MINOR_SEVENTH, 4),
+            step(PERF5, &DOMINANT_SEVENTH, 4),
+            step(PERF1, &MAJOR_SEVENTH, 8),
+        ],
+    };
+
+    pub static ref MINOR_II_V_I: Progression = Progression {
+        name: "minor ii-V-i",
+        mode: KeyMode::Minor,
+        steps: vec![
+            step(MAJ2, &HALF_DIMINISHED_SEVENTH, 4),
+            step_with_tensions(PERF5, &DOMINANT_SEVENTH, vec![TFLAT9], 4),
+            step(PERF1, &MINOR_SIXTH, 8),
+        ],
+    };
+
+    // Turnarounds
+
+    pub static ref I_VI_II_V: Progression = Progression {
+        name: "I-vi-ii-V turnaround",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(PERF1, &MAJOR_SEVENTH, 2),
+            step(MAJ6, &MINOR_SEVENTH, 2),
+            step(MAJ2, &MINOR_SEVENTH, 2),
+            step(PERF5, &DOMINANT_SEVENTH, 2),
+        ],
+    };
+
+    // The I-vi-ii-V with every chord turned into a dominant leading to the next.
+    pub static ref I_VI_II_V_DOMINANTS: Progression = Progression {
+        name: "I-VI7-II7-V7 turnaround",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(PERF1, &MAJOR_SEVENTH, 2),
+            step(MAJ6, &DOMINANT_SEVENTH, 2),
+            step(MAJ2, &DOMINANT_SEVENTH, 2),
+            step(PERF5, &DOMINANT_SEVENTH, 2),
+        ],
+    };
+
+    pub static ref III_VI_II_V: Progression = Progression {
+        name: "iii-vi-ii-V turnaround",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(MAJ3, &MINOR_SEVENTH, 2),
+            step(MAJ6, &DOMINANT_SEVENTH, 2),
+            step(MAJ2, &MINOR_SEVENTH, 2),
+            step(PERF5, &DOMINANT_SEVENTH, 2),
+        ],
+    };
+
+    // Blues
+
+    pub static ref BASIC_BLUES: Progression = Progression {
+        name: "12-bar blues",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(PERF1, &DOMINANT_SEVENTH, 4),
+            step(PERF4, &DOMINANT_SEVENTH, 4),
+            step(PERF1, &DOMINANT_SEVENTH, 8),
+            step(PERF4, &DOMINANT_SEVENTH, 8),
+            step(PERF1, &DOMINANT_SEVENTH, 8),
+            step(PERF5, &DOMINANT_SEVENTH, 4),
+            step(PERF4, &DOMINANT_SEVENTH, 4),
+            step(PERF1, &DOMINANT_SEVENTH, 4),
+            step(PERF5, &DOMINANT_SEVENTH, 4),
+        ],
+    };
+
+    pub static ref JAZZ_BLUES: Progression = Progression {
+        name: "jazz blues",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(PERF1, &DOMINANT_SEVENTH, 4),
+            step(PERF4, &DOMINANT_SEVENTH, 4),
+            step(PERF1, &DOMINANT_SEVENTH, 4),
+            step(PERF5, &MINOR_SEVENTH, 2),
+            step(PERF1, &DOMINANT_SEVENTH, 2),
+            step(PERF4, &DOMINANT_SEVENTH, 4),
+            step(TRITONE4, &DIMINISHED_SEVENTH, 4),
+            step(PERF1, &DOMINANT_SEVENTH, 4),
+            step(MAJ6, &DOMINANT_SEVENTH, 4),
+            step(MAJ2, &MINOR_SEVENTH, 4),
+            step(PERF5, &DOMINANT_SEVENTH, 4),
+            step(PERF1, &DOMINANT_SEVENTH, 2),
+            step(MAJ6, &DOMINANT_SEVENTH, 2),
+            step(MAJ2, &MINOR_SEVENTH, 2),
+            step(PERF5, &DOMINANT_SEVENTH, 2),
+        ],
+    };
+
+    pub static ref MINOR_BLUES: Progression = Progression {
+        name: "minor blues",
+        mode: KeyMode::Minor,
+        steps: vec![
+            step(PERF1, &MINOR_SEVENTH, 4),
+            step(PERF4, &MINOR_SEVENTH, 4),
+            step(PERF1, &MINOR_SEVENTH, 8),
+            step(PERF4, &MINOR_SEVENTH, 8),
+            step(PERF1, &MINOR_SEVENTH, 8),
+            step(MIN6, &DOMINANT_SEVENTH, 4),
+            step_with_tensions(PERF5, &DOMINANT_SEVENTH, vec![TSHARP9], 4),
+            step(PERF1, &MINOR_SEVENTH, 4),
+            step(MAJ2, &HALF_DIMINISHED_SEVENTH, 2),
+            step(PERF5, &DOMINANT_SEVENTH, 2),
+        ],
+    };
+
+    // Forms from tunes
+
+    // The bridge of "I Got Rhythm": each dominant lasts two bars and resolves down a 5th to the
+    // next, landing on V7 to return to the A section.
+    pub static ref RHYTHM_CHANGES_BRIDGE: Progression = Progression {
+        name: "rhythm changes bridge",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(MAJ3, &DOMINANT_SEVENTH, 8),
+            step(MAJ6, &DOMINANT_SEVENTH, 8),
+            step(MAJ2, &DOMINANT_SEVENTH, 8),
+            step(PERF5, &DOMINANT_SEVENTH, 8),
+        ],
+    };
+
+    // Coltrane's substitution over a ii-V-I, as in "Countdown": the V7 is replaced by a cycle of
+    // key centers a major third apart (bVI, III, then I), each approached by its own dominant.
+    pub static ref COLTRANE_CHANGES: Progression = Progression {
+        name: "Coltrane changes",
+        mode: KeyMode::Major,
+        steps: vec![
+            step(MAJ2, &MINOR_SEVENTH, 2),
+            step(MIN3, &DOMINANT_SEVENTH, 2),
+            step(MIN6, &MAJOR_SEVENTH, 2),
+            step(MAJ7, &DOMINANT_SEVENTH, 2),
+            step(MAJ3, &MAJOR_SEVENTH, 2),
+            step(PERF5, &DOMINANT_SEVENTH, 2),
+            step(PERF1, &MAJOR_SEVENTH, 4),
+        ],
+    };
+
+    // Every progression above, for anything that needs to iterate the whole library.
+    pub static ref ALL_PROGRESSIONS: Vec<&'static Progression> = vec![
+        &MAJOR_II_V_I,
+        &MINOR_II_V_I,
+        &I_VI_II_V,
+        &I_VI_II_V_DOMINANTS,
+        &III_VI_II_V,
+        &BASIC_BLUES,
+        &JAZZ_BLUES,
+        &MINOR_BLUES,
+        &RHYTHM_CHANGES_BRIDGE,
+        &COLTRANE_CHANGES,
+    ];
+}
+
+impl Progression {
+    // The chords of the progression played from root, like Dm7 G7 Cmaj7 for a major ii-V-I from
+    // C.
+    pub fn chords(&self, root: &Note) -> Vec<ChordSymbol> {
+        self.try_chords(root)
+            .unwrap_or_else(|err| panic!("Couldn't play {} from {root}: {err:?}", self.name))
+    }
+
+    // Like chords, but returns an error instead of panicking if a chord root would need more than
+    // a double accidental.
+    pub fn try_chords(&self, root: &Note) -> Result<Vec<ChordSymbol>, ApplyIntervalError> {
+        self.steps
+            .iter()
+            .map(|step| {
+                Ok(ChordSymbol {
+                    root: root.try_apply_interval(&step.interval)?,
+                    quality: step.quality,
+                    tensions: step.tensions.clone(),
+                    altered: false,
+                    bass: None,
+                })
+            })
+            .collect()
+    }
+
+    // The progression in each of the 12 keys we drill, from 5 flats to 6 sharps, so major
+    // progressions go from Db to F#, and minor ones from Bb minor to D# minor. Every chord in
+    // these keys can be spelled with at most a double accidental.
+    pub fn in_all_keys(&self) -> Vec<(Key, Vec<ChordSymbol>)> {
+        (-5..=6)
+            .map(|fifths| {
+                let key = Key::from_fifths(fifths, self.mode).unwrap();
+                (key, self.chords(&key.tonic()))
+            })
+            .collect()
+    }
+
+    // How many beats the whole progression lasts.
+    pub fn beats(&self) -> u32 {
+        self.steps.iter().map(|step| step.beats as u32).sum()
+    }
+
+    // Writes the progression from root as a one-line lead sheet, with a bar line every 4 beats
+    // and "%" for a bar that repeats the previous chord: "| Dm7 | G7 | Cmaj7 | % |".
+    pub fn lead_sheet(&self, root: &Note) -> Result<String, ApplyIntervalError> {
+        let chords = self.try_chords(root)?;
+
+        let mut bars: Vec<Vec<String>> = vec![];
+        let mut beat = 0;
+        for (step, chord) in self.steps.iter().zip(chords.iter()) {
+            for offset in 0..step.beats {
+                if beat % BEATS_PER_BAR == 0 {
+                    bars.push(vec![]);
+                }
+
+                let bar = bars.last_mut().unwrap();
+                if offset == 0 {
+                    bar.push(chord.to_string());
+                } else if bar.is_empty() {
+                    bar.push("%".to_string());
+                }
+
+                beat = (beat + 1) % BEATS_PER_BAR;
+            }
+        }
+
+        let bars: Vec<String> = bars.iter().map(|bar| bar.join(" ")).collect();
+        Ok(format!("| {} |", bars.join(" | ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn chords(progression: &Progression, root: &str) -> Vec<String> {
+        progression
+            .chords(&Note::from_str(root).unwrap())
+            .iter()
+            .map(|chord| chord.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_ii_v_i() {
+        assert_eq!(chords(&MAJOR_II_V_I, "C"), ["Dm7", "G7", "Cmaj7"]);
+        assert_eq!(chords(&MAJOR_II_V_I, "Db"), ["Ebm7", "Ab7", "Dbmaj7"]);
+        assert_eq!(chords(&MINOR_II_V_I, "C"), ["Dm7b5", "G7(b9)", "Cm6"]);
+        assert_eq!(chords(&MINOR_II_V_I, "F#"), ["G#m7b5", "C#7(b9)", "F#m6"]);
+    }
+
+    #[test]
+    fn test_forms() {
+        assert_eq!(
+            chords(&RHYTHM_CHANGES_BRIDGE, "Bb"),
+            ["D7", "G7", "C7", "F7"]
+        );
+        assert_eq!(
+            chords(&COLTRANE_CHANGES, "C"),
+            ["Dm7", "Eb7", "Abmaj7", "B7", "Emaj7", "G7", "Cmaj7"]
+        );
+        assert_eq!(chords(&JAZZ_BLUES, "F")[6], "Bdim7");
+    }
+
+    #[test]
+    fn test_lengths() {
+        assert_eq!(BASIC_BLUES.beats(), 48);
+        assert_eq!(JAZZ_BLUES.beats(), 48);
+        assert_eq!(MINOR_BLUES.beats(), 48);
+        assert_eq!(RHYTHM_CHANGES_BRIDGE.beats(), 32);
+
+        for progression in ALL_PROGRESSIONS.iter() {
+            assert_eq!(
+                progression.beats() % BEATS_PER_BAR as u32,
+                0,
+                "{} doesn't fill its last bar",
+                progression.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_lead_sheet() {
+        let c = Note::from_str("C").unwrap();
+        assert_eq!(
+            MAJOR_II_V_I.lead_sheet(&c),
+            Ok("| Dm7 | G7 | Cmaj7 | % |".to_string())
+        );
+        assert_eq!(
+            JAZZ_BLUES.lead_sheet(&Note::from_str("Bb").unwrap()),
+            Ok(
+                "| Bb7 | Eb7 | Bb7 | Fm7 Bb7 | Eb7 | Edim7 | Bb7 | G7 | Cm7 | F7 | Bb7 G7 | Cm7 F7 |"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_every_progression_in_every_key() {
+        for progression in ALL_PROGRESSIONS.iter() {
+            let keys = progression.in_all_keys();
+            assert_eq!(keys.len(), 12);
+
+            let mut pitch_classes: Vec<i8> = keys
+                .iter()
+                .map(|(key, _)| key.tonic().intra_octave_semitone_value())
+                .collect();
+            pitch_classes.sort();
+            pitch_classes.dedup();
+            assert_eq!(pitch_classes.len(), 12);
+
+            for (key, chords) in keys {
+                assert_eq!(chords.len(), progression.steps.len());
+                assert_eq!(
+                    progression.try_chords(&key.tonic()),
+                    Ok(chords),
+                    "{} in {key}",
+                    progression.name
+                );
+            }
+        }
+    }
+}
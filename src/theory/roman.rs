@@ -0,0 +1,489 @@
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+
+use crate::theory::chord::*;
+use crate::theory::harmony::{harmonize, ChordSize};
+use crate::theory::interval::{Interval, MIN2, MIN3, PERF4, TRITONE5};
+use crate::theory::key::Key;
+use crate::theory::note::{ApplyIntervalError, Note};
+use crate::theory::scale::IONIAN;
+
+// A chord written relative to a key, like ii7, V7/V or subV7.
+//
+// Degrees are always numbered against the major scale of the key's tonic, even in minor keys, so
+// the chord on the 3rd of C minor is bIII and the chord on the 7th is bVII. Chords with a minor
+// 3rd are written with lowercase numerals, and the quality follows the numeral (vii°, iiø7, IV6,
+// V+7).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RomanNumeral {
+    // How many semitones the root is raised or lowered from the major scale degree, so bVII is -1.
+    pub alteration: i8,
+
+    // The scale degree, from 1 to 7.
+    pub degree: usize,
+
+    pub quality: &'static ChordQuality,
+
+    // Whether this is the tritone substitute of the chord written, like the subV7 that resolves to
+    // I the same way V7 does.
+    pub tritone_substitute: bool,
+
+    // The chord this one belongs to, if it's a secondary chord like V7/V. The degree is then
+    // counted from the target's root instead of the tonic.
+    pub target: Option<Box<RomanNumeral>>,
+}
+
+const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+
+// Ordered so that longer numerals are tried before the numerals they start with.
+const NUMERALS_BY_LENGTH: [(&str, usize); 7] = [
+    ("VII", 7),
+    ("VI", 6),
+    ("V", 5),
+    ("IV", 4),
+    ("III", 3),
+    ("II", 2),
+    ("I", 1),
+];
+
+lazy_static! {
+    // How each quality is written after a numeral. Qualities whose case and suffix would clash
+    // (m7 and 7 are both "7") are told apart by the numeral's case. The first suffix listed for a
+    // quality is the one we write.
+    static ref NUMERAL_SUFFIXES: Vec<(&'static str, &'static ChordQuality)> = vec![
+        ("", &MAJOR_TRIAD),
+        ("", &MINOR_TRIAD),
+        ("°", &DIMINISHED_TRIAD),
+        ("o", &DIMINISHED_TRIAD),
+        ("+", &AUGMENTED_TRIAD),
+        ("sus2", &SUS2),
+        ("sus4", &SUS4),
+        ("6", &MAJOR_SIXTH),
+        ("6", &MINOR_SIXTH),
+        ("maj7", &MAJOR_SEVENTH),
+        ("7", &DOMINANT_SEVENTH),
+        ("7", &MINOR_SEVENTH),
+        ("ø7", &HALF_DIMINISHED_SEVENTH),
+        ("ø", &HALF_DIMINISHED_SEVENTH),
+        ("7b5", &HALF_DIMINISHED_SEVENTH),
+        ("°7", &DIMINISHED_SEVENTH),
+        ("o7", &DIMINISHED_SEVENTH),
+        ("maj7", &MINOR_MAJOR_SEVENTH),
+        ("7sus4", &DOMINANT_SEVENTH_SUS4),
+        ("+7", &AUGMENTED_SEVENTH),
+        ("7b5", &DOMINANT_SEVENTH_FLAT_FIVE),
+        ("+maj7", &AUGMENTED_MAJOR_SEVENTH),
+    ];
+}
+
+fn is_minor(quality: &ChordQuality) -> bool {
+    quality.intervals[1] == MIN3
+}
+
+impl RomanNumeral {
+    // The chord this numeral stands for in the given key, like Dm7 for ii7 in C major. Fails if a
+    // root would need more than a double accidental, like V7/#iv in C# major.
+    pub fn chord_in(&self, key: &Key) -> Result<ChordSymbol, ApplyIntervalError> {
+        let tonic = match &self.target {
+            Some(target) => target.root_from(&key.tonic())?,
+            None => key.tonic(),
+        };
+
+        Ok(ChordSymbol {
+            root: self.root_from(&tonic)?,
+            quality: self.quality,
+            tensions: vec![],
+            altered: false,
+            bass: None,
+        })
+    }
+
+    // The root of this numeral counted from tonic, ignoring any target.
+    fn root_from(&self, tonic: &Note) -> Result<Note, ApplyIntervalError> {
+        let major_degree = IONIAN.ascending[self.degree - 1];
+
+        // Every major scale interval is nameable with up to two semitones of alteration.
+        let interval = Interval::from_number_and_semitones(
+            major_degree.number,
+            major_degree.semitones + self.alteration,
+        )
+        .unwrap();
+
+        let root = tonic.try_apply_interval(&interval)?;
+        if self.tritone_substitute {
+            root.try_apply_interval(&TRITONE5)
+        } else {
+            Ok(root)
+        }
+    }
+
+    // Analyzes the chord in the given key, ignoring its tensions:
+    //
+    //  - Chords in the key's scale (with stacked thirds) get their plain numeral, like ii7.
+    //  - Dominant chords that resolve down a 5th to a chord in the scale other than the tonic are
+    //    secondary dominants, like V7/V.
+    //  - Dominant chords that resolve down a half step to a chord in the scale are tritone
+    //    substitutions, like subV7 or subV7/ii.
+    //  - Anything else, like a chord borrowed from the parallel key, gets a plain numeral with
+    //    the alteration it needs, like bVII or iv6.
+    //
+    // Returns None if the root is so far from the key that it can't be written with a double
+    // accidental at most.
+    pub fn analyze(symbol: &ChordSymbol, key: &Key) -> Option<RomanNumeral> {
+        let plain = RomanNumeral::from_root(&key.tonic(), &symbol.root, symbol.quality)?;
+        if is_diatonic(symbol, key) || !symbol.quality.is_dominant() {
+            return Some(plain);
+        }
+
+        let triads = harmonize(&key.tonic(), key.scale(), ChordSize::Triad).ok()?;
+        let diatonic_target = |root: Note| {
+            triads
+                .iter()
+                .find(|triad| triad.root().name() == root.name())
+                .and_then(|triad| triad.roman_numeral())
+        };
+
+        if let Ok(target_root) = symbol.root.try_apply_interval(&PERF4) {
+            if target_root.name() != key.tonic().name() {
+                if let Some(target) = diatonic_target(target_root) {
+                    return Some(RomanNumeral {
+                        target: Some(Box::new(target)),
+                        ..RomanNumeral::from_root(&target_root, &symbol.root, symbol.quality)?
+                    });
+                }
+            }
+        }
+
+        if let Ok(target_root) = symbol.root.try_apply_interval_below(&MIN2) {
+            if let Some(target) = diatonic_target(target_root) {
+                let dominant = target_root.try_apply_interval_below(&PERF4).ok()?;
+                let target = if target.degree == 1 && target.alteration == 0 {
+                    None
+                } else {
+                    Some(Box::new(target))
+                };
+
+                return Some(RomanNumeral {
+                    tritone_substitute: true,
+                    target,
+                    ..RomanNumeral::from_root(&target_root, &dominant, symbol.quality)?
+                });
+            }
+        }
+
+        Some(plain)
+    }
+
+    // The numeral of a chord on root, counted from tonic.
+    fn from_root(
+        tonic: &Note,
+        root: &Note,
+        quality: &'static ChordQuality,
+    ) -> Option<RomanNumeral> {
+        let degree = (root.letter() as i8 - tonic.letter() as i8).rem_euclid(7) as usize;
+        let semitones = (root.intra_octave_semitone_value() - tonic.intra_octave_semitone_value())
+            .rem_euclid(12);
+
+        // Wrap around the octave, so Cb in C is a flatted 1st rather than an 11-semitone one.
+        let alteration = match semitones - IONIAN.ascending[degree].semitones {
+            a if a > 6 => a - 12,
+            a if a < -6 => a + 12,
+            a => a,
+        };
+        if !(-2..=2).contains(&alteration) {
+            return None;
+        }
+
+        Some(RomanNumeral {
+            alteration,
+            degree: degree + 1,
+            quality,
+            tritone_substitute: false,
+            target: None,
+        })
+    }
+}
+
+// Whether the chord is built from stacked thirds of the key's scale.
+fn is_diatonic(symbol: &ChordSymbol, key: &Key) -> bool {
+    let size = match symbol.quality.intervals.len() {
+        3 => ChordSize::Triad,
+        4 => ChordSize::Seventh,
+        _ => return false,
+    };
+
+    harmonize(&key.tonic(), key.scale(), size)
+        .map(|chords| {
+            chords.iter().any(|chord| {
+                chord.root().name() == symbol.root.name() && chord.quality == Some(symbol.quality)
+            })
+        })
+        .unwrap_or(false)
+}
+
+impl std::fmt::Display for RomanNumeral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.tritone_substitute {
+            write!(f, "sub")?;
+        }
+
+        let accidental = match self.alteration {
+            -2 => "bb",
+            -1 => "b",
+            1 => "#",
+            2 => "##",
+            _ => "",
+        };
+
+        let numeral = NUMERALS[self.degree - 1];
+        let numeral = if is_minor(self.quality) {
+            numeral.to_lowercase()
+        } else {
+            numeral.to_string()
+        };
+
+        let suffix = NUMERAL_SUFFIXES
+            .iter()
+            .find(|(_, quality)| *quality == self.quality)
+            .map_or("", |(suffix, _)| suffix);
+
+        write!(f, "{accidental}{numeral}{suffix}")?;
+
+        if let Some(target) = &self.target {
+            write!(f, "/{target}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RomanNumeralParseError {
+    // There's no numeral from I to VII (or i to vii) where one is expected, or it mixes cases.
+    InvalidNumeral,
+    // What follows the numeral isn't a quality we know for a numeral of that case, like "ii+".
+    InvalidQuality,
+    // The part after the slash isn't a plain numeral, like "V7/V7/V".
+    InvalidTarget,
+}
+
+impl FromStr for RomanNumeral {
+    type Err = RomanNumeralParseError;
+
+    // Parses numerals like "ii7", "bVII", "iv6", "V7/V", "vii°7/ii", "subV7" and "subV7/ii".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (numeral, target) = match s.split_once('/') {
+            Some((numeral, target)) => {
+                let target =
+                    parse_numeral(target).map_err(|_| RomanNumeralParseError::InvalidTarget)?;
+                if target.tritone_substitute {
+                    return Err(RomanNumeralParseError::InvalidTarget);
+                }
+                (numeral, Some(Box::new(target)))
+            }
+            None => (s, None),
+        };
+
+        Ok(RomanNumeral {
+            target,
+            ..parse_numeral(numeral)?
+        })
+    }
+}
+
+// Parses a numeral without a target.
+fn parse_numeral(s: &str) -> Result<RomanNumeral, RomanNumeralParseError> {
+    let (tritone_substitute, mut rest) = match s.strip_prefix("sub") {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+
+    let mut alteration = 0;
+    loop {
+        if let Some(stripped) = rest.strip_prefix('b').or_else(|| rest.strip_prefix('♭')) {
+            alteration -= 1;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix('#').or_else(|| rest.strip_prefix('♯')) {
+            alteration += 1;
+            rest = stripped;
+        } else {
+            break;
+        }
+    }
+    if !(-2..=2).contains(&alteration) {
+        return Err(RomanNumeralParseError::InvalidNumeral);
+    }
+
+    let (numeral, degree) = NUMERALS_BY_LENGTH
+        .iter()
+        .find_map(|(numeral, degree)| {
+            let lowercase = numeral.to_lowercase();
+            if rest.starts_with(numeral) {
+                Some((numeral.to_string(), *degree))
+            } else if rest.starts_with(&lowercase) {
+                Some((lowercase, *degree))
+            } else {
+                None
+            }
+        })
+        .ok_or(RomanNumeralParseError::InvalidNumeral)?;
+
+    let lowercase = numeral.chars().all(|c| c.is_lowercase());
+    let suffix = &rest[numeral.len()..];
+    let quality = NUMERAL_SUFFIXES
+        .iter()
+        .find(|(written, quality)| *written == suffix && is_minor(quality) == lowercase)
+        .map(|(_, quality)| *quality)
+        .ok_or(RomanNumeralParseError::InvalidQuality)?;
+
+    Ok(RomanNumeral {
+        alteration,
+        degree,
+        quality,
+        tritone_substitute,
+        target: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numeral(s: &str) -> RomanNumeral {
+        RomanNumeral::from_str(s).unwrap()
+    }
+
+    fn chord_in(s: &str, key: &str) -> String {
+        numeral(s)
+            .chord_in(&Key::from_str(key).unwrap())
+            .unwrap()
+            .to_string()
+    }
+
+    fn analyze(symbol: &str, key: &str) -> String {
+        RomanNumeral::analyze(
+            &ChordSymbol::from_str(symbol).unwrap(),
+            &Key::from_str(key).unwrap(),
+        )
+        .unwrap()
+        .to_string()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for s in [
+            "I",
+            "ii7",
+            "iii",
+            "IVmaj7",
+            "V7",
+            "vi",
+            "vii°",
+            "viiø7",
+            "bVII",
+            "iv6",
+            "V7/V",
+            "vii°7/V",
+            "subV7",
+            "subV7/ii",
+            "bIII+maj7",
+            "imaj7",
+            "V7sus4",
+            "#iv°",
+            "I+",
+            "bbvii°7",
+        ] {
+            assert_eq!(numeral(s).to_string(), s);
+        }
+
+        assert_eq!(numeral("viio7").to_string(), "vii°7");
+        assert_eq!(numeral("ii7b5").to_string(), "iiø7");
+        assert_eq!(numeral("♭VII").to_string(), "bVII");
+
+        let v_of_v = numeral("V7/V");
+        assert_eq!(v_of_v.quality, &*DOMINANT_SEVENTH);
+        assert_eq!(v_of_v.target.unwrap().quality, &*MAJOR_TRIAD);
+        assert_eq!(numeral("ii7").quality, &*MINOR_SEVENTH);
+        assert_eq!(numeral("II7").quality, &*DOMINANT_SEVENTH);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            RomanNumeral::from_str("H7"),
+            Err(RomanNumeralParseError::InvalidNumeral)
+        );
+        assert_eq!(
+            RomanNumeral::from_str("bbbII"),
+            Err(RomanNumeralParseError::InvalidNumeral)
+        );
+        assert_eq!(
+            RomanNumeral::from_str("ii+"),
+            Err(RomanNumeralParseError::InvalidQuality)
+        );
+        assert_eq!(
+            RomanNumeral::from_str("Vm"),
+            Err(RomanNumeralParseError::InvalidQuality)
+        );
+        assert_eq!(
+            RomanNumeral::from_str("V7/V7/V"),
+            Err(RomanNumeralParseError::InvalidTarget)
+        );
+        assert_eq!(
+            RomanNumeral::from_str("V7/subV"),
+            Err(RomanNumeralParseError::InvalidTarget)
+        );
+    }
+
+    #[test]
+    fn test_chords_in_key() {
+        assert_eq!(chord_in("ii7", "C major"), "Dm7");
+        assert_eq!(chord_in("V7/V", "C major"), "D7");
+        assert_eq!(chord_in("vii°7/V", "C major"), "F#dim7");
+        assert_eq!(chord_in("bVII", "C major"), "Bb");
+        assert_eq!(chord_in("iv6", "C major"), "Fm6");
+        assert_eq!(chord_in("subV7", "C major"), "Db7");
+        assert_eq!(chord_in("subV7/ii", "C major"), "Eb7");
+        assert_eq!(chord_in("V7/vi", "Eb major"), "G7");
+        assert_eq!(chord_in("IVmaj7", "Gb major"), "Cbmaj7");
+        assert_eq!(chord_in("bIIImaj7", "A minor"), "Cmaj7");
+        assert_eq!(chord_in("V7", "A minor"), "E7");
+    }
+
+    #[test]
+    fn test_analyze() {
+        assert_eq!(analyze("Dm7", "C major"), "ii7");
+        assert_eq!(analyze("G7(9,13)", "C major"), "V7");
+        assert_eq!(analyze("Bm7b5", "C major"), "viiø7");
+        assert_eq!(analyze("D7", "C major"), "V7/V");
+        assert_eq!(analyze("E7", "C major"), "V7/vi");
+        assert_eq!(analyze("A7", "C major"), "V7/ii");
+        assert_eq!(analyze("C7", "C major"), "V7/IV");
+        assert_eq!(analyze("Db7", "C major"), "subV7");
+        assert_eq!(analyze("Eb7", "C major"), "subV7/ii");
+        assert_eq!(analyze("Bb", "C major"), "bVII");
+        assert_eq!(analyze("Fm6", "C major"), "iv6");
+        assert_eq!(analyze("Abmaj7", "C major"), "bVImaj7");
+
+        assert_eq!(analyze("Cm7", "C minor"), "i7");
+        assert_eq!(analyze("Ebmaj7", "C minor"), "bIIImaj7");
+        assert_eq!(analyze("G7", "C minor"), "V7");
+        assert_eq!(analyze("F#7", "E major"), "V7/V");
+    }
+
+    #[test]
+    fn test_round_trip_through_key() {
+        let key = Key::from_str("Bb major").unwrap();
+        for s in [
+            "ii7", "V7", "Imaj7", "V7/ii", "V7/V", "subV7", "bVII", "iv6",
+        ] {
+            let symbol = numeral(s).chord_in(&key).unwrap();
+            assert_eq!(
+                RomanNumeral::analyze(&symbol, &key).unwrap().to_string(),
+                s,
+                "{symbol}"
+            );
+        }
+    }
+}
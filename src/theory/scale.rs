@@ -1,41 +1,437 @@
 use crate::theory::interval::*;
 use lazy_static::lazy_static;
 
+#[derive(PartialEq, Eq, Debug)]
 pub struct Scale {
+    // The canonical name of the scale, like "lydian dominant".
+    pub name: &'static str,
+
+    // Other names the scale goes by, like "lydian b7" or "overtone".
+    pub aliases: Vec<&'static str>,
+
     pub ascending: Vec<Interval>,
 
     // If None, the descending notes are the same as the ascending notes.
-    // If Some, that indicates that the descending notes differ from the ascending notes.
+    // If Some, that indicates that the descending notes differ from the ascending notes. They're
+    // listed from the top down, ending on PERF1, and don't include the octave.
     pub descending: Option<Vec<Interval>>,
 }
 
 lazy_static! {
     pub static ref CHROMATIC: Scale = Scale {
+        name: "chromatic",
+        aliases: vec![],
         ascending: vec![
             PERF1, MIN2, MAJ2, MIN3, MAJ3, PERF4, TRITONE5, PERF5, MIN6, MAJ6, MIN7, MAJ7,
         ],
         descending: None,
     };
 
+    // Modes of the major scale
+
+    pub static ref IONIAN: Scale = Scale {
+        name: "ionian",
+        aliases: vec!["major"],
+        ascending: vec![PERF1, MAJ2, MAJ3, PERF4, PERF5, MAJ6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref DORIAN: Scale = Scale {
+        name: "dorian",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MIN3, PERF4, PERF5, MAJ6, MIN7],
+        descending: None,
+    };
+
+    pub static ref PHRYGIAN: Scale = Scale {
+        name: "phrygian",
+        aliases: vec![],
+        ascending: vec![PERF1, MIN2, MIN3, PERF4, PERF5, MIN6, MIN7],
+        descending: None,
+    };
+
+    pub static ref LYDIAN: Scale = Scale {
+        name: "lydian",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MAJ3, TRITONE4, PERF5, MAJ6, MAJ7],
+        descending: None,
+    };
+
     pub static ref MIXO: Scale = Scale {
+        name: "mixolydian",
+        aliases: vec!["mixo", "dominant"],
         ascending: vec![PERF1, MAJ2, MAJ3, PERF4, PERF5, MAJ6, MIN7],
         descending: None,
     };
 
-    pub static ref MIXO_FLAT9: Scale = Scale {
-        ascending: vec![PERF1, MIN2, MAJ3, PERF4, PERF5, MAJ6, MIN7],
+    pub static ref AEOLIAN: Scale = Scale {
+        name: "aeolian",
+        aliases: vec!["natural minor", "minor"],
+        ascending: vec![PERF1, MAJ2, MIN3, PERF4, PERF5, MIN6, MIN7],
+        descending: None,
+    };
+
+    pub static ref LOCRIAN: Scale = Scale {
+        name: "locrian",
+        aliases: vec![],
+        ascending: vec![PERF1, MIN2, MIN3, PERF4, TRITONE5, MIN6, MIN7],
+        descending: None,
+    };
+
+    // Modes of melodic minor
+
+    pub static ref MELODIC_MINOR: Scale = Scale {
+        name: "melodic minor",
+        aliases: vec!["jazz minor"],
+        ascending: vec![PERF1, MAJ2, MIN3, PERF4, PERF5, MAJ6, MAJ7],
+        descending: None,
+    };
+
+    // Melodic minor as it's taught classically: the 6th and 7th are raised on the way up and
+    // lowered back to natural minor on the way down.
+    pub static ref CLASSICAL_MELODIC_MINOR: Scale = Scale {
+        name: "classical melodic minor",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MIN3, PERF4, PERF5, MAJ6, MAJ7],
+        descending: Some(vec![MIN7, MIN6, PERF5, PERF4, MIN3, MAJ2, PERF1]),
+    };
+
+    pub static ref DORIAN_FLAT2: Scale = Scale {
+        name: "dorian b2",
+        aliases: vec!["phrygian #6", "phrygian nat6"],
+        ascending: vec![PERF1, MIN2, MIN3, PERF4, PERF5, MAJ6, MIN7],
+        descending: None,
+    };
+
+    pub static ref LYDIAN_AUGMENTED: Scale = Scale {
+        name: "lydian augmented",
+        aliases: vec!["lydian #5"],
+        ascending: vec![PERF1, MAJ2, MAJ3, TRITONE4, AUG5, MAJ6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref LYDIAN_DOMINANT: Scale = Scale {
+        name: "lydian dominant",
+        aliases: vec!["lydian b7", "mixolydian #11", "mixo #11", "overtone"],
+        ascending: vec![PERF1, MAJ2, MAJ3, TRITONE4, PERF5, MAJ6, MIN7],
         descending: None,
     };
 
     pub static ref MIXO_FLAT13: Scale = Scale {
+        name: "mixolydian b13",
+        aliases: vec!["mixo b13", "mixolydian b6", "aeolian dominant"],
         ascending: vec![PERF1, MAJ2, MAJ3, PERF4, PERF5, MIN6, MIN7],
         descending: None,
     };
 
+    pub static ref LOCRIAN_NAT2: Scale = Scale {
+        name: "locrian nat2",
+        aliases: vec!["locrian #2", "aeolian b5", "half diminished"],
+        ascending: vec![PERF1, MAJ2, MIN3, PERF4, TRITONE5, MIN6, MIN7],
+        descending: None,
+    };
+
     // "The LAST thing I'm thinking about on G ALT is G! That shit's Ab melodic minor *stomp*!"
     // -- Ed Tomassi
     pub static ref ALTERED: Scale = Scale {
+        name: "altered",
+        aliases: vec!["alt", "super locrian", "diminished whole tone"],
         ascending: vec![PERF1, MIN2, AUG2, MAJ3, TRITONE5, MIN6, MIN7],
         descending: None,
     };
+
+    // Modes of harmonic minor
+
+    pub static ref HARMONIC_MINOR: Scale = Scale {
+        name: "harmonic minor",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MIN3, PERF4, PERF5, MIN6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref LOCRIAN_NAT6: Scale = Scale {
+        name: "locrian nat6",
+        aliases: vec!["locrian #6"],
+        ascending: vec![PERF1, MIN2, MIN3, PERF4, TRITONE5, MAJ6, MIN7],
+        descending: None,
+    };
+
+    pub static ref IONIAN_AUGMENTED: Scale = Scale {
+        name: "ionian augmented",
+        aliases: vec!["ionian #5"],
+        ascending: vec![PERF1, MAJ2, MAJ3, PERF4, AUG5, MAJ6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref DORIAN_SHARP4: Scale = Scale {
+        name: "dorian #4",
+        aliases: vec!["dorian #11", "ukrainian dorian", "romanian minor"],
+        ascending: vec![PERF1, MAJ2, MIN3, TRITONE4, PERF5, MAJ6, MIN7],
+        descending: None,
+    };
+
+    pub static ref PHRYGIAN_DOMINANT: Scale = Scale {
+        name: "phrygian dominant",
+        aliases: vec!["mixolydian b9 b13", "mixo b9 b13", "spanish phrygian"],
+        ascending: vec![PERF1, MIN2, MAJ3, PERF4, PERF5, MIN6, MIN7],
+        descending: None,
+    };
+
+    pub static ref LYDIAN_SHARP2: Scale = Scale {
+        name: "lydian #2",
+        aliases: vec!["lydian #9"],
+        ascending: vec![PERF1, AUG2, MAJ3, TRITONE4, PERF5, MAJ6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref ULTRALOCRIAN: Scale = Scale {
+        name: "ultralocrian",
+        aliases: vec!["super locrian bb7", "altered bb7"],
+        ascending: vec![PERF1, MIN2, MIN3, DIM4, TRITONE5, MIN6, DIM7],
+        descending: None,
+    };
+
+    // Harmonic major and its modes
+
+    pub static ref HARMONIC_MAJOR: Scale = Scale {
+        name: "harmonic major",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MAJ3, PERF4, PERF5, MIN6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref MIXO_FLAT9: Scale = Scale {
+        name: "mixolydian b9",
+        aliases: vec!["mixo b9", "mixolydian b2"],
+        ascending: vec![PERF1, MIN2, MAJ3, PERF4, PERF5, MAJ6, MIN7],
+        descending: None,
+    };
+
+    // Symmetric scales
+
+    pub static ref WHOLE_TONE: Scale = Scale {
+        name: "whole tone",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MAJ3, TRITONE4, AUG5, MIN7],
+        descending: None,
+    };
+
+    // Eight notes don't fit in seven letters, so one letter repeats in both diminished scales; we
+    // follow the usual spellings rather than reaching for diminished 7ths.
+    pub static ref DIMINISHED_WHOLE_HALF: Scale = Scale {
+        name: "whole-half diminished",
+        aliases: vec!["diminished", "whole half"],
+        ascending: vec![PERF1, MAJ2, MIN3, PERF4, TRITONE5, MIN6, MAJ6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref DIMINISHED_HALF_WHOLE: Scale = Scale {
+        name: "half-whole diminished",
+        aliases: vec!["dominant diminished", "half whole"],
+        ascending: vec![PERF1, MIN2, AUG2, MAJ3, TRITONE4, PERF5, MAJ6, MIN7],
+        descending: None,
+    };
+
+    // Bebop scales
+
+    pub static ref BEBOP_DOMINANT: Scale = Scale {
+        name: "bebop dominant",
+        aliases: vec!["bebop"],
+        ascending: vec![PERF1, MAJ2, MAJ3, PERF4, PERF5, MAJ6, MIN7, MAJ7],
+        descending: None,
+    };
+
+    pub static ref BEBOP_MAJOR: Scale = Scale {
+        name: "bebop major",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MAJ3, PERF4, PERF5, AUG5, MAJ6, MAJ7],
+        descending: None,
+    };
+
+    pub static ref BEBOP_DORIAN: Scale = Scale {
+        name: "bebop dorian",
+        aliases: vec!["bebop minor"],
+        ascending: vec![PERF1, MAJ2, MIN3, MAJ3, PERF4, PERF5, MAJ6, MIN7],
+        descending: None,
+    };
+
+    // Blues and pentatonics
+
+    pub static ref BLUES: Scale = Scale {
+        name: "blues",
+        aliases: vec!["minor blues"],
+        ascending: vec![PERF1, MIN3, PERF4, TRITONE5, PERF5, MIN7],
+        descending: None,
+    };
+
+    pub static ref MAJOR_BLUES: Scale = Scale {
+        name: "major blues",
+        aliases: vec![],
+        ascending: vec![PERF1, MAJ2, MIN3, MAJ3, PERF5, MAJ6],
+        descending: None,
+    };
+
+    pub static ref MAJOR_PENTATONIC: Scale = Scale {
+        name: "major pentatonic",
+        aliases: vec!["pentatonic"],
+        ascending: vec![PERF1, MAJ2, MAJ3, PERF5, MAJ6],
+        descending: None,
+    };
+
+    pub static ref MINOR_PENTATONIC: Scale = Scale {
+        name: "minor pentatonic",
+        aliases: vec![],
+        ascending: vec![PERF1, MIN3, PERF4, PERF5, MIN7],
+        descending: None,
+    };
+
+    // Every scale above, for anything that needs to iterate the whole catalog.
+    pub static ref ALL_SCALES: Vec<&'static Scale> = vec![
+        &CHROMATIC,
+        &IONIAN,
+        &DORIAN,
+        &PHRYGIAN,
+        &LYDIAN,
+        &MIXO,
+        &AEOLIAN,
+        &LOCRIAN,
+        &MELODIC_MINOR,
+        &DORIAN_FLAT2,
+        &LYDIAN_AUGMENTED,
+        &LYDIAN_DOMINANT,
+        &MIXO_FLAT13,
+        &LOCRIAN_NAT2,
+        &ALTERED,
+        &HARMONIC_MINOR,
+        &LOCRIAN_NAT6,
+        &IONIAN_AUGMENTED,
+        &DORIAN_SHARP4,
+        &PHRYGIAN_DOMINANT,
+        &LYDIAN_SHARP2,
+        &ULTRALOCRIAN,
+        &HARMONIC_MAJOR,
+        &MIXO_FLAT9,
+        &WHOLE_TONE,
+        &DIMINISHED_WHOLE_HALF,
+        &DIMINISHED_HALF_WHOLE,
+        &BEBOP_DOMINANT,
+        &BEBOP_MAJOR,
+        &BEBOP_DORIAN,
+        &BLUES,
+        &MAJOR_BLUES,
+        &MAJOR_PENTATONIC,
+        &MINOR_PENTATONIC,
+    ];
+
+    // Scales that only differ from one in ALL_SCALES on the way down. They're left out of
+    // ALL_SCALES so that searching the catalog by notes doesn't find the same scale twice, but
+    // scale_by_name still finds them.
+    pub static ref DESCENDING_VARIANTS: Vec<&'static Scale> = vec![&CLASSICAL_MELODIC_MINOR];
+}
+
+// Looks up a scale by its name or any of its aliases. The lookup ignores case, spacing, and
+// hyphens, and accepts spelled-out or Unicode accidentals, so "Lydian ♭7", "lydian flat 7" and
+// "lydianb7" all find lydian dominant.
+pub fn scale_by_name(name: &str) -> Option<&'static Scale> {
+    let wanted = normalize_scale_name(name);
+
+    ALL_SCALES
+        .iter()
+        .chain(DESCENDING_VARIANTS.iter())
+        .copied()
+        .find(|scale| {
+            std::iter::once(&scale.name)
+                .chain(scale.aliases.iter())
+                .any(|candidate| normalize_scale_name(candidate) == wanted)
+        })
+}
+
+fn normalize_scale_name(name: &str) -> String {
+    name.to_lowercase()
+        .replace("natural", "nat")
+        .replace("flat", "b")
+        .replace("sharp", "#")
+        .replace('♮', "nat")
+        .replace('♭', "b")
+        .replace('♯', "#")
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_scale_by_name() {
+        assert_eq!(scale_by_name("lydian b7"), Some(&*LYDIAN_DOMINANT));
+        assert_eq!(scale_by_name("Lydian ♭7"), Some(&*LYDIAN_DOMINANT));
+        assert_eq!(scale_by_name("super locrian"), Some(&*ALTERED));
+        assert_eq!(scale_by_name("Locrian natural 2"), Some(&*LOCRIAN_NAT2));
+        assert_eq!(scale_by_name("whole-tone"), Some(&*WHOLE_TONE));
+        assert_eq!(scale_by_name("Mixolydian"), Some(&*MIXO));
+        assert_eq!(scale_by_name("natural minor"), Some(&*AEOLIAN));
+        assert_eq!(scale_by_name("not a scale"), None);
+        assert_eq!(
+            scale_by_name("classical melodic minor"),
+            Some(&*CLASSICAL_MELODIC_MINOR)
+        );
+    }
+
+    #[test]
+    fn test_catalog_has_no_repeated_scales() {
+        let mut seen = HashSet::new();
+
+        for scale in ALL_SCALES.iter() {
+            let semitones: Vec<i8> = scale.ascending.iter().map(|i| i.semitones).collect();
+            assert!(seen.insert(semitones), "{} is listed twice", scale.name);
+        }
+    }
+
+    #[test]
+    fn test_names_are_unambiguous() {
+        let mut seen = HashSet::new();
+
+        for scale in ALL_SCALES.iter().chain(DESCENDING_VARIANTS.iter()) {
+            for name in std::iter::once(&scale.name).chain(scale.aliases.iter()) {
+                assert!(
+                    seen.insert(normalize_scale_name(name)),
+                    "{name} names more than one scale"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_scales_start_on_the_root() {
+        for scale in ALL_SCALES.iter().chain(DESCENDING_VARIANTS.iter()) {
+            assert_eq!(scale.ascending[0], PERF1, "{} has no root", scale.name);
+
+            let semitones: Vec<i8> = scale.ascending.iter().map(|i| i.semitones).collect();
+            assert!(
+                semitones.windows(2).all(|pair| pair[0] < pair[1]),
+                "{} doesn't ascend",
+                scale.name
+            );
+
+            if let Some(descending) = &scale.descending {
+                assert_eq!(
+                    descending.last(),
+                    Some(&PERF1),
+                    "{} has no root",
+                    scale.name
+                );
+
+                let semitones: Vec<i8> = descending.iter().map(|i| i.semitones).collect();
+                assert!(
+                    semitones.windows(2).all(|pair| pair[0] > pair[1]),
+                    "{} doesn't descend",
+                    scale.name
+                );
+            }
+        }
+    }
 }
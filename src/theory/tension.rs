@@ -0,0 +1,210 @@
+use crate::theory::chord::ChordQuality;
+use crate::theory::interval::*;
+use crate::theory::note::{ApplyIntervalError, Note};
+use crate::theory::scale::Scale;
+
+// ChordTensions splits the non-chord tones of a chord-scale into available tensions and avoid
+// notes, following Berklee-style chord-scale theory:
+//
+//  - A scale tone a whole step above a chord tone is an available tension.
+//  - A scale tone a half step above a chord tone is an avoid note.
+//  - Dominant chords are the exception: b9, #9 and b13 are always available on them, even though
+//    b9 and b13 sit a half step above the root and the fifth.
+//
+// Anything else (like the 3rd over a sus4 chord) is also an avoid note.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ChordTensions {
+    // Available tensions, written with the tension constants (T9, TSHARP11, etc.) where one
+    // exists. Like those constants, they're kept within the octave.
+    pub available: Vec<Interval>,
+
+    // Avoid notes, written as the scale intervals they came from (e.g. PERF4 over a maj7).
+    pub avoid: Vec<Interval>,
+}
+
+impl ChordTensions {
+    // Spells the available tensions above the given chord root.
+    pub fn available_notes(&self, root: &Note) -> Vec<Note> {
+        self.try_available_notes(root)
+            .unwrap_or_else(|err| panic!("Couldn't spell tensions above {root}: {err:?}"))
+    }
+
+    // Like available_notes, but returns an error instead of panicking if any tension would need
+    // more than a double accidental, like the 9 above B##.
+    pub fn try_available_notes(&self, root: &Note) -> Result<Vec<Note>, ApplyIntervalError> {
+        self.available
+            .iter()
+            .map(|tension| root.try_apply_interval(tension))
+            .collect()
+    }
+
+    // Spells the avoid notes above the given chord root.
+    pub fn avoid_notes(&self, root: &Note) -> Vec<Note> {
+        self.try_avoid_notes(root)
+            .unwrap_or_else(|err| panic!("Couldn't spell avoid notes above {root}: {err:?}"))
+    }
+
+    // Like avoid_notes, but returns an error instead of panicking if any avoid note would need
+    // more than a double accidental.
+    pub fn try_avoid_notes(&self, root: &Note) -> Result<Vec<Note>, ApplyIntervalError> {
+        self.avoid
+            .iter()
+            .map(|avoid| root.try_apply_interval(avoid))
+            .collect()
+    }
+}
+
+impl ChordQuality {
+    // Tensions and avoid notes over this chord, using its default chord-scale.
+    pub fn tensions(&self) -> ChordTensions {
+        self.tensions_in(self.chord_scale)
+    }
+
+    // Tensions and avoid notes over this chord when the given scale is played over it, like a
+    // dominant seventh with the altered scale.
+    pub fn tensions_in(&self, scale: &Scale) -> ChordTensions {
+        let chord_tones: Vec<i8> = self
+            .intervals
+            .iter()
+            .map(|interval| interval.semitones % 12)
+            .collect();
+        let is_chord_tone = |semitones: i8| chord_tones.contains(&semitones.rem_euclid(12));
+
+        let mut tensions = ChordTensions {
+            available: vec![],
+            avoid: vec![],
+        };
+
+        for interval in scale.ascending.iter() {
+            let semitones = interval.semitones % 12;
+            if is_chord_tone(semitones) {
+                continue;
+            }
+
+            let dominant_exception = self.is_dominant() && matches!(semitones, 1 | 3 | 8);
+            let half_step_above = is_chord_tone(semitones - 1);
+            let whole_step_above = is_chord_tone(semitones - 2);
+
+            if dominant_exception || (whole_step_above && !half_step_above) {
+                tensions.available.push(as_tension(interval));
+            } else {
+                tensions.avoid.push(*interval);
+            }
+        }
+
+        tensions
+    }
+
+    // Whether the chord functions as a dominant: it has a minor 7th and no minor 3rd. This
+    // includes 7sus4, 7#5 and 7b5 chords.
+    pub fn is_dominant(&self) -> bool {
+        self.intervals.contains(&MIN7) && !self.intervals.contains(&MIN3)
+    }
+}
+
+// Rewrites a scale interval as the tension it's called over a chord, so the #4 of lydian becomes a
+// #11 and the b5 of the altered scale also becomes a #11. Intervals that aren't 9ths, 11ths or
+// 13ths (like the major 7th over a diminished seventh chord) are returned unchanged.
+fn as_tension(interval: &Interval) -> Interval {
+    match (interval.number, interval.semitones % 12) {
+        (1, 1) => TFLAT9,
+        (1, 2) => T9,
+        (1, 3) => TSHARP9,
+        (3, 5) => T11,
+        (3, 6) | (4, 6) => TSHARP11,
+        (4, 8) | (5, 8) => TFLAT13,
+        (5, 9) => T13,
+        _ => *interval,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::theory::chord::*;
+    use crate::theory::note::NoteLetter;
+    use crate::theory::scale::{ALTERED, LYDIAN};
+
+    use super::*;
+
+    #[test]
+    fn test_default_chord_scales() {
+        let maj7 = MAJOR_SEVENTH.tensions();
+        assert_eq!(maj7.available, vec![T9, T13]);
+        assert_eq!(maj7.avoid, vec![PERF4]);
+
+        let dom7 = DOMINANT_SEVENTH.tensions();
+        assert_eq!(dom7.available, vec![T9, T13]);
+        assert_eq!(dom7.avoid, vec![PERF4]);
+
+        let min7 = MINOR_SEVENTH.tensions();
+        assert_eq!(min7.available, vec![T9, T11, T13]);
+        assert!(min7.avoid.is_empty());
+
+        let half_dim = HALF_DIMINISHED_SEVENTH.tensions();
+        assert_eq!(half_dim.available, vec![T11, TFLAT13]);
+        assert_eq!(half_dim.avoid, vec![MIN2]);
+
+        let dim7 = DIMINISHED_SEVENTH.tensions();
+        assert_eq!(dim7.available, vec![T9, T11, TFLAT13, MAJ7]);
+
+        let sus = DOMINANT_SEVENTH_SUS4.tensions();
+        assert_eq!(sus.available, vec![T9, T13]);
+        assert_eq!(sus.avoid, vec![MAJ3]);
+    }
+
+    #[test]
+    fn test_explicit_chord_scales() {
+        assert_eq!(
+            MAJOR_SEVENTH.tensions_in(&LYDIAN).available,
+            vec![T9, TSHARP11, T13]
+        );
+        assert_eq!(
+            DOMINANT_SEVENTH.tensions_in(&ALTERED),
+            ChordTensions {
+                available: vec![TFLAT9, TSHARP9, TSHARP11, TFLAT13],
+                avoid: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_spelled_tensions() {
+        let db = Note::from_str("Db").unwrap();
+        let tensions: Vec<String> = MAJOR_SEVENTH
+            .tensions()
+            .available_notes(&db)
+            .iter()
+            .map(|note| note.to_string())
+            .collect();
+        assert_eq!(tensions, ["Eb4", "Bb4"]);
+
+        let g = Note::from_str("G").unwrap();
+        let altered: Vec<String> = DOMINANT_SEVENTH
+            .tensions_in(&ALTERED)
+            .available_notes(&g)
+            .iter()
+            .map(|note| note.to_string())
+            .collect();
+        assert_eq!(altered, ["Ab4", "A#4", "C#5", "Eb5"]);
+
+        // The 9 of B##maj7 would be C with a triple sharp, but its avoid note is only E##.
+        let b_double_sharp = Note::from_str("B##").unwrap();
+        let maj7 = MAJOR_SEVENTH.tensions();
+        assert_eq!(
+            maj7.try_available_notes(&b_double_sharp),
+            Err(ApplyIntervalError::TooManyAccidentals {
+                letter: NoteLetter::C,
+                offset: 3
+            })
+        );
+        let avoid: Vec<String> = maj7
+            .try_avoid_notes(&b_double_sharp)
+            .unwrap()
+            .iter()
+            .map(|note| note.to_string())
+            .collect();
+        assert_eq!(avoid, ["E##5"]);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::theory::chord::ChordSymbol;
+use crate::theory::interval::*;
+use crate::theory::note::{ApplyIntervalError, Note};
+use crate::theory::voicing::VoicingRange;
+
+// How far a voice may move between two chords. No pitch class is more than 6 semitones away, so
+// this only comes into play when the range or the other voices get in the way.
+const MAX_VOICE_MOTION: i16 = 12;
+
+// Constraints on the voicings the solver may pick.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VoiceLeadingOptions {
+    pub range: VoicingRange,
+
+    // Whether to reject voicings with parallel perfect 5ths or octaves between any two voices.
+    pub avoid_parallels: bool,
+}
+
+// Where one voice goes from one chord to the next.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VoiceMotion {
+    pub from: Note,
+    pub to: Note,
+}
+
+impl VoiceMotion {
+    // Positive when the voice moves up, negative when it moves down.
+    pub fn semitones(&self) -> i16 {
+        self.to.inter_octave_semitone_value() - self.from.inter_octave_semitone_value()
+    }
+}
+
+// The voicing picked for one chord, along with how each voice got there from the previous one.
+// Voices are numbered from the bottom up, in both `notes` and `motion`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VoiceLeadingStep {
+    pub symbol: ChordSymbol,
+    pub notes: Vec<Note>,
+    pub motion: Vec<VoiceMotion>,
+}
+
+impl VoiceLeadingStep {
+    // The total number of semitones moved by all of the voices.
+    pub fn total_motion(&self) -> u32 {
+        self.motion
+            .iter()
+            .map(|motion| motion.semitones().unsigned_abs() as u32)
+            .sum()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VoiceLeadingError {
+    // The starting voicing has notes outside of the range, or isn't in ascending order.
+    InvalidStart,
+    // No voicing of the chord at this index (into the chords passed in) satisfies the options.
+    NoVoicing { chord: usize },
+    // One of the chord's notes needs more than a double accidental.
+    Unspellable(ApplyIntervalError),
+}
+
+impl From<ApplyIntervalError> for VoiceLeadingError {
+    fn from(err: ApplyIntervalError) -> Self {
+        VoiceLeadingError::Unspellable(err)
+    }
+}
+
+// Voices each chord in turn so that the voices move as few semitones as possible in total,
+// starting from the given voicing. Every voicing has as many voices as the start, stays in the
+// range and never crosses voices. Returns one step per chord.
+//
+// Each voicing includes the chord's 3rd and 7th (the root and 3rd of a triad), then as many of the
+// written tensions as there's room for. The other voices can take any chord tone or tension,
+// where seventh chords without written tensions use the available tensions of their chord-scale.
+pub fn lead_voices(
+    start: &[Note],
+    chords: &[ChordSymbol],
+    options: &VoiceLeadingOptions,
+) -> Result<Vec<VoiceLeadingStep>, VoiceLeadingError> {
+    let ascending = start
+        .windows(2)
+        .all(|pair| pair[0].inter_octave_semitone_value() < pair[1].inter_octave_semitone_value());
+    if !ascending || !start.iter().all(|note| options.range.contains(note)) {
+        return Err(VoiceLeadingError::InvalidStart);
+    }
+
+    let mut steps = Vec::with_capacity(chords.len());
+    let mut previous = start.to_vec();
+    for (index, symbol) in chords.iter().enumerate() {
+        let notes = next_voicing(&previous, symbol, options)?
+            .ok_or(VoiceLeadingError::NoVoicing { chord: index })?;
+
+        steps.push(VoiceLeadingStep {
+            symbol: symbol.clone(),
+            motion: previous
+                .iter()
+                .zip(notes.iter())
+                .map(|(from, to)| VoiceMotion {
+                    from: *from,
+                    to: *to,
+                })
+                .collect(),
+            notes: notes.clone(),
+        });
+        previous = notes;
+    }
+
+    Ok(steps)
+}
+
+// Whether any two voices move in parallel perfect 5ths or octaves (or unisons) from one voicing
+// to the next. A pair only counts if both of its voices move.
+pub fn has_parallels(from: &[Note], to: &[Note]) -> bool {
+    let pitch = |note: &Note| note.inter_octave_semitone_value();
+
+    for lower in 0..from.len().min(to.len()) {
+        for upper in lower + 1..from.len().min(to.len()) {
+            let before = (pitch(&from[upper]) - pitch(&from[lower])).rem_euclid(12);
+            let after = (pitch(&to[upper]) - pitch(&to[lower])).rem_euclid(12);
+            let both_move = pitch(&from[lower]) != pitch(&to[lower])
+                && pitch(&from[upper]) != pitch(&to[upper]);
+
+            if both_move && before == after && (before == 0 || before == 7) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+// The intervals above the root that voicings of the chord must include, in order of importance,
+// and the ones they may include.
+fn chord_tones(symbol: &ChordSymbol) -> (Vec<Interval>, Vec<Interval>) {
+    let intervals = &symbol.quality.intervals;
+    let mut required = match intervals.get(3) {
+        Some(seventh) => vec![intervals[1], *seventh],
+        None => vec![intervals[1], intervals[0]],
+    };
+
+    let tensions = if symbol.altered {
+        vec![TFLAT9, TSHARP9, TSHARP11, TFLAT13]
+    } else if symbol.tensions.is_empty() && intervals.len() > 3 {
+        symbol.quality.tensions().available
+    } else if symbol.tensions.is_empty() {
+        vec![]
+    } else {
+        required.extend(symbol.tensions.iter());
+        symbol.tensions.clone()
+    };
+
+    let mut allowed = intervals.clone();
+    if symbol.altered {
+        allowed.retain(|interval| *interval != PERF5);
+    }
+    allowed.extend(tensions);
+
+    (required, allowed)
+}
+
+struct Search<'a> {
+    previous: &'a [Note],
+    candidates: Vec<Vec<Note>>,
+    required: Vec<i8>,
+    distinct: bool,
+    avoid_parallels: bool,
+    best: Option<(u32, Vec<Note>)>,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, chosen: &mut Vec<Note>, cost: u32) {
+        if let Some((best_cost, _)) = &self.best {
+            if cost >= *best_cost {
+                return;
+            }
+        }
+
+        let voice = chosen.len();
+        if voice == self.previous.len() {
+            if self.is_valid(chosen) {
+                self.best = Some((cost, chosen.clone()));
+            }
+            return;
+        }
+
+        for index in 0..self.candidates[voice].len() {
+            let note = self.candidates[voice][index];
+            if let Some(below) = chosen.last() {
+                if note.inter_octave_semitone_value() <= below.inter_octave_semitone_value() {
+                    continue;
+                }
+            }
+
+            let motion = note.inter_octave_semitone_value()
+                - self.previous[voice].inter_octave_semitone_value();
+            chosen.push(note);
+            self.visit(chosen, cost + motion.unsigned_abs() as u32);
+            chosen.pop();
+        }
+    }
+
+    fn is_valid(&self, notes: &[Note]) -> bool {
+        let pitch_classes: Vec<i8> = notes
+            .iter()
+            .map(|note| note.intra_octave_semitone_value())
+            .collect();
+
+        if !self.required.iter().all(|pc| pitch_classes.contains(pc)) {
+            return false;
+        }
+        if self.distinct
+            && (1..pitch_classes.len()).any(|i| pitch_classes[..i].contains(&pitch_classes[i]))
+        {
+            return false;
+        }
+
+        !(self.avoid_parallels && has_parallels(self.previous, notes))
+    }
+}
+
+// The voicing of the chord closest to the previous one, or None if nothing satisfies the options.
+fn next_voicing(
+    previous: &[Note],
+    symbol: &ChordSymbol,
+    options: &VoiceLeadingOptions,
+) -> Result<Option<Vec<Note>>, VoiceLeadingError> {
+    let (required, allowed) = chord_tones(symbol);
+
+    let spell = |intervals: &[Interval]| -> Result<Vec<Note>, ApplyIntervalError> {
+        intervals
+            .iter()
+            .map(|interval| symbol.root.try_apply_interval(interval))
+            .collect()
+    };
+    let required: Vec<i8> = spell(&required)?
+        .iter()
+        .take(previous.len())
+        .map(|note| note.intra_octave_semitone_value())
+        .collect();
+
+    let mut names: Vec<Note> = vec![];
+    for name in spell(&allowed)? {
+        let pitch_class = name.intra_octave_semitone_value();
+        if !names
+            .iter()
+            .any(|other| other.intra_octave_semitone_value() == pitch_class)
+        {
+            names.push(name);
+        }
+    }
+
+    // Each voice can move to any allowed note nearby, trying the smallest moves first so that
+    // ties go to the voicing found first.
+    let candidates = previous
+        .iter()
+        .map(|from| {
+            let mut notes: Vec<Note> = names
+                .iter()
+                .flat_map(|name| {
+                    (-2..=2).filter_map(move |octaves: i8| {
+                        Some(name.with_octave(from.octave().checked_add(octaves)?))
+                    })
+                })
+                .filter(|note| {
+                    let motion =
+                        note.inter_octave_semitone_value() - from.inter_octave_semitone_value();
+                    motion.abs() <= MAX_VOICE_MOTION && options.range.contains(note)
+                })
+                .collect();
+            notes.sort_by_key(|note| {
+                let motion =
+                    note.inter_octave_semitone_value() - from.inter_octave_semitone_value();
+                (motion.abs(), *note)
+            });
+            notes
+        })
+        .collect();
+
+    let mut search = Search {
+        previous,
+        candidates,
+        required,
+        distinct: previous.len() <= names.len(),
+        avoid_parallels: options.avoid_parallels,
+        best: None,
+    };
+    search.visit(&mut Vec::with_capacity(previous.len()), 0);
+
+    Ok(search.best.map(|(_, notes)| notes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn notes(names: &str) -> Vec<Note> {
+        names
+            .split(' ')
+            .map(|name| Note::from_str(name).unwrap())
+            .collect()
+    }
+
+    fn chords(symbols: &[&str]) -> Vec<ChordSymbol> {
+        symbols
+            .iter()
+            .map(|symbol| ChordSymbol::from_str(symbol).unwrap())
+            .collect()
+    }
+
+    fn options(low: &str, high: &str, avoid_parallels: bool) -> VoiceLeadingOptions {
+        VoiceLeadingOptions {
+            range: VoicingRange {
+                low: Note::from_str(low).unwrap(),
+                high: Note::from_str(high).unwrap(),
+            },
+            avoid_parallels,
+        }
+    }
+
+    #[test]
+    fn test_two_five_one() {
+        let steps = lead_voices(
+            &notes("F3 A3 C4 E4"),
+            &chords(&["G7", "Cmaj7"]),
+            &options("C3", "C5", false),
+        )
+        .unwrap();
+
+        // Only the 7th of Dm7 moves, down to the 3rd of G7.
+        assert_eq!(steps[0].notes, notes("F3 A3 B3 E4"));
+        let motion: Vec<i16> = steps[0].motion.iter().map(|m| m.semitones()).collect();
+        assert_eq!(motion, [0, 0, -1, 0]);
+        assert_eq!(steps[0].total_motion(), 1);
+
+        // The 3rd and 13th of G7 hold as the 7th and 3rd of Cmaj7, and only the bottom voice moves.
+        assert_eq!(steps[1].notes, notes("G3 A3 B3 E4"));
+        assert_eq!(steps[1].total_motion(), 2);
+    }
+
+    #[test]
+    fn test_written_tensions() {
+        let steps = lead_voices(
+            &notes("F3 A3 B3 E4"),
+            &chords(&["G7(b9,b13)"]),
+            &options("C3", "C5", false),
+        )
+        .unwrap();
+
+        assert_eq!(steps[0].notes, notes("F3 Ab3 B3 Eb4"));
+    }
+
+    #[test]
+    fn test_parallels() {
+        let c_to_d = |avoid_parallels| {
+            lead_voices(
+                &notes("C4 E4 G4"),
+                &chords(&["D"]),
+                &options("C3", "C6", avoid_parallels),
+            )
+            .unwrap()
+        };
+
+        let free = c_to_d(false);
+        assert_eq!(free[0].total_motion(), 6);
+
+        let strict = c_to_d(true);
+        assert_eq!(strict[0].notes, notes("A3 D4 F#4"));
+        assert_eq!(strict[0].total_motion(), 6);
+        assert!(!has_parallels(&notes("C4 E4 G4"), &strict[0].notes));
+
+        assert!(has_parallels(&notes("C4 E4 G4"), &notes("D4 F#4 A4")));
+        assert!(!has_parallels(&notes("C4 G4"), &notes("C4 A4")));
+    }
+
+    #[test]
+    fn test_constraints() {
+        assert_eq!(
+            lead_voices(
+                &notes("E4 C4"),
+                &chords(&["F"]),
+                &options("C3", "C6", false)
+            ),
+            Err(VoiceLeadingError::InvalidStart)
+        );
+        assert_eq!(
+            lead_voices(
+                &notes("C4 E4 G4"),
+                &chords(&["F"]),
+                &options("C4", "F4", false)
+            ),
+            Err(VoiceLeadingError::InvalidStart)
+        );
+
+        // Only C4 to E4 is available, which can't hold both the 3rd and the root of B major.
+        assert_eq!(
+            lead_voices(
+                &notes("C4 D4"),
+                &chords(&["C", "B"]),
+                &options("C4", "E4", false)
+            ),
+            Err(VoiceLeadingError::NoVoicing { chord: 1 })
+        );
+    }
+}
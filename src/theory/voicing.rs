@@ -0,0 +1,463 @@
+use std::str::FromStr;
+
+use crate::theory::chord::ChordSymbol;
+use crate::theory::interval::*;
+use crate::theory::note::{ApplyIntervalError, Note};
+
+// The families of voicings we know how to build. Each family can have several forms, like the
+// four inversions of a drop-2 voicing.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VoicingType {
+    // Root, 3rd and 7th, in either order above the root: R-3-7 and R-7-3.
+    Shell,
+
+    // A close-position seventh chord with the second voice from the top dropped an octave, in
+    // all four inversions. A 9 replaces the root and a 13 or 11 replaces the 5th, if the chord has
+    // them.
+    Drop2,
+
+    // Like Drop2, but dropping the third voice from the top.
+    Drop3,
+
+    // Bill Evans' rootless voicings with the 3rd on the bottom: 3-5-7-9, or 3-13-7-9 on dominant
+    // chords.
+    RootlessA,
+
+    // The other rootless voicing, with the 7th on the bottom: 7-9-3-5, or 7-9-3-13 on dominant
+    // chords.
+    RootlessB,
+
+    // The "So What" voicing: three perfect 4ths and a major 3rd, using only notes of the chord's
+    // scale (no avoid notes), and including its 3rd and 7th. Dm7 is D G C F A, and Cmaj7 is
+    // E A D G B.
+    SoWhat,
+
+    // A major triad over the 3rd and 7th of a dominant chord, like a D triad over C7 for
+    // C7(9,#11,13). Only triads whose notes are tensions of the chord (or chord tones) are used.
+    UpperStructure,
+}
+
+// A voicing of a chord: its notes, from the bottom up.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Voicing {
+    pub kind: VoicingType,
+    pub notes: Vec<Note>,
+}
+
+// The lowest and highest notes a voicing may use, inclusive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VoicingRange {
+    pub low: Note,
+    pub high: Note,
+}
+
+impl VoicingRange {
+    // The two octaves from C4 to B5 that the Piano draws.
+    pub fn piano() -> VoicingRange {
+        VoicingRange {
+            low: Note::from_str("C4").unwrap(),
+            high: Note::from_str("B5").unwrap(),
+        }
+    }
+
+    pub fn contains(&self, note: &Note) -> bool {
+        self.low.inter_octave_semitone_value() <= note.inter_octave_semitone_value()
+            && note.inter_octave_semitone_value() <= self.high.inter_octave_semitone_value()
+    }
+
+    // Moves the notes by whole octaves so that the bottom note is as low as the range allows.
+    // Returns None if the notes don't fit.
+    fn place(&self, notes: &[Note]) -> Option<Vec<Note>> {
+        let bottom = notes.first()?;
+        let distance =
+            self.low.inter_octave_semitone_value() - bottom.inter_octave_semitone_value();
+        let octaves = i8::try_from((distance + 11).div_euclid(12)).ok()?;
+
+        let placed: Vec<Note> = notes
+            .iter()
+            .map(|note| Some(note.with_octave(note.octave().checked_add(octaves)?)))
+            .collect::<Option<_>>()?;
+
+        if placed.iter().all(|note| self.contains(note)) {
+            Some(placed)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VoicingError {
+    // The voicing type needs a 7th (or a 6th in its place), but the chord is a triad.
+    NotASeventhChord,
+    // Upper structures only go over dominant chords with a major 3rd.
+    NotDominant,
+    // One of the notes needs more than a double accidental.
+    Unspellable(ApplyIntervalError),
+}
+
+impl From<ApplyIntervalError> for VoicingError {
+    fn from(err: ApplyIntervalError) -> Self {
+        VoicingError::Unspellable(err)
+    }
+}
+
+// The roots of the major triads used as upper structures, from the root of the dominant chord:
+// II, bIII, bV, bVI and VI.
+const UPPER_STRUCTURE_ROOTS: [Interval; 5] = [MAJ2, MIN3, TRITONE5, MIN6, MAJ6];
+
+// Every form of the given voicing type for the chord that fits in the range, each placed as low
+// as the range allows. Forms that don't fit are left out, so this can return no voicings.
+pub fn voicings(
+    symbol: &ChordSymbol,
+    kind: VoicingType,
+    range: &VoicingRange,
+) -> Result<Vec<Voicing>, VoicingError> {
+    let forms = match kind {
+        VoicingType::Shell => shell_forms(symbol)?,
+        VoicingType::Drop2 => drop_forms(symbol, 2)?,
+        VoicingType::Drop3 => drop_forms(symbol, 1)?,
+        VoicingType::RootlessA => vec![rootless_form(symbol, true)?],
+        VoicingType::RootlessB => vec![rootless_form(symbol, false)?],
+        VoicingType::SoWhat => so_what_forms(symbol)?,
+        VoicingType::UpperStructure => upper_structure_forms(symbol)?,
+    };
+
+    Ok(forms
+        .iter()
+        .filter_map(|names| range.place(&stack(names)))
+        .map(|notes| Voicing { kind, notes })
+        .collect())
+}
+
+// Places the note names from the bottom up, each one as close as possible above the last.
+fn stack(names: &[Note]) -> Vec<Note> {
+    let mut notes: Vec<Note> = Vec::with_capacity(names.len());
+    for name in names {
+        let mut note = match notes.last() {
+            Some(below) => name.with_octave(below.octave() - 1),
+            None => name.with_octave(0),
+        };
+        while let Some(below) = notes.last() {
+            if note.inter_octave_semitone_value() > below.inter_octave_semitone_value() {
+                break;
+            }
+            note = note.with_octave(note.octave() + 1);
+        }
+        notes.push(note);
+    }
+    notes
+}
+
+// The chord's root, 3rd, 5th and 7th, or an error if it has no 7th.
+fn seventh_chord(symbol: &ChordSymbol) -> Result<[Interval; 4], VoicingError> {
+    match symbol.quality.intervals[..] {
+        [root, third, fifth, seventh] => Ok([root, third, fifth, seventh]),
+        _ => Err(VoicingError::NotASeventhChord),
+    }
+}
+
+// The chord's first tension with the given number (1 for 9ths, 3 for 11ths, 5 for 13ths).
+fn tension(symbol: &ChordSymbol, number: i8) -> Option<Interval> {
+    symbol
+        .tensions
+        .iter()
+        .find(|tension| tension.number % 7 == number)
+        .copied()
+}
+
+fn names(symbol: &ChordSymbol, intervals: &[Interval]) -> Result<Vec<Note>, VoicingError> {
+    Ok(intervals
+        .iter()
+        .map(|interval| symbol.root.try_apply_interval(interval))
+        .collect::<Result<_, _>>()?)
+}
+
+fn shell_forms(symbol: &ChordSymbol) -> Result<Vec<Vec<Note>>, VoicingError> {
+    let [root, third, _, seventh] = seventh_chord(symbol)?;
+
+    Ok(vec![
+        names(symbol, &[root, third, seventh])?,
+        names(symbol, &[root, seventh, third])?,
+    ])
+}
+
+// Drops the voice `dropped` places from the bottom of each close-position inversion: 2 is the
+// second voice from the top for drop-2, and 1 the third from the top for drop-3.
+fn drop_forms(symbol: &ChordSymbol, dropped: usize) -> Result<Vec<Vec<Note>>, VoicingError> {
+    let [root, third, fifth, seventh] = seventh_chord(symbol)?;
+    let bottom = tension(symbol, 1).unwrap_or(root);
+    let middle = tension(symbol, 5)
+        .filter(|_| !symbol.quality.intervals.contains(&MAJ6))
+        .or_else(|| tension(symbol, 3))
+        .unwrap_or(fifth);
+
+    let close = names(symbol, &[bottom, third, middle, seventh])?;
+
+    Ok((0..close.len())
+        .map(|inversion| {
+            let mut voices: Vec<Note> = close[inversion..]
+                .iter()
+                .chain(close[..inversion].iter())
+                .copied()
+                .collect();
+
+            // Moving a voice to the bottom of the list and restacking drops it below the rest.
+            let dropped_voice = voices.remove(dropped);
+            voices.insert(0, dropped_voice);
+            voices
+        })
+        .collect())
+}
+
+fn rootless_form(symbol: &ChordSymbol, third_on_bottom: bool) -> Result<Vec<Note>, VoicingError> {
+    let [root, third, fifth, seventh] = seventh_chord(symbol)?;
+
+    // Without a 9 written, use the 9 if the chord-scale allows it, and otherwise double the root.
+    let ninth = tension(symbol, 1).unwrap_or_else(|| {
+        if symbol.quality.tensions().available.contains(&T9) {
+            T9
+        } else {
+            root
+        }
+    });
+    let fifth = if symbol.quality.is_dominant() {
+        tension(symbol, 5).unwrap_or(T13)
+    } else {
+        fifth
+    };
+
+    if third_on_bottom {
+        names(symbol, &[third, fifth, seventh, ninth])
+    } else {
+        names(symbol, &[seventh, ninth, third, fifth])
+    }
+}
+
+fn so_what_forms(symbol: &ChordSymbol) -> Result<Vec<Vec<Note>>, VoicingError> {
+    let [_, third, _, seventh] = seventh_chord(symbol)?;
+    let quality = symbol.quality;
+    let avoid = quality.tensions().avoid;
+
+    let pitch_class = |note: &Note| note.intra_octave_semitone_value();
+    let usable: Vec<i8> = quality
+        .chord_scale
+        .ascending
+        .iter()
+        .filter(|interval| !avoid.contains(interval))
+        .map(|interval| {
+            symbol
+                .root
+                .try_apply_interval(interval)
+                .map(|n| pitch_class(&n))
+        })
+        .collect::<Result<_, _>>()?;
+    let required = [
+        pitch_class(&symbol.root.try_apply_interval(&third)?),
+        pitch_class(&symbol.root.try_apply_interval(&seventh)?),
+    ];
+
+    let mut forms = vec![];
+    for bottom in quality.chord_scale.ascending.iter() {
+        let mut form = vec![symbol.root.try_apply_interval(bottom)?];
+        for interval in [PERF4, PERF4, PERF4, MAJ3] {
+            let next = form.last().unwrap().try_apply_interval(&interval)?;
+            form.push(next);
+        }
+
+        let pitch_classes: Vec<i8> = form.iter().map(pitch_class).collect();
+        if pitch_classes.iter().all(|pc| usable.contains(pc))
+            && required.iter().all(|pc| pitch_classes.contains(pc))
+        {
+            forms.push(form);
+        }
+    }
+
+    Ok(forms)
+}
+
+fn upper_structure_forms(symbol: &ChordSymbol) -> Result<Vec<Vec<Note>>, VoicingError> {
+    let [root, third, fifth, seventh] = seventh_chord(symbol)?;
+    if !symbol.quality.is_dominant() || third != MAJ3 {
+        return Err(VoicingError::NotDominant);
+    }
+
+    // The notes a triad may use, in semitones above the root. Without any tensions written, any
+    // dominant tension will do. An alt chord has no natural 5th.
+    let tensions: Vec<Interval> = if symbol.altered {
+        vec![TFLAT9, TSHARP9, TSHARP11, TFLAT13]
+    } else if symbol.tensions.is_empty() {
+        vec![TFLAT9, T9, TSHARP9, TSHARP11, TFLAT13, T13]
+    } else {
+        symbol.tensions.clone()
+    };
+    let mut allowed: Vec<i8> = tensions.iter().map(|t| t.semitones % 12).collect();
+    allowed.extend([root, third, seventh].iter().map(|i| i.semitones % 12));
+    if !symbol.altered {
+        allowed.push(fifth.semitones % 12);
+    }
+
+    let mut forms = vec![];
+    for triad_root in UPPER_STRUCTURE_ROOTS.iter() {
+        let triad = [
+            *triad_root,
+            triad_root.checked_add(&MAJ3).unwrap(),
+            triad_root.checked_add(&PERF5).unwrap(),
+        ];
+        if triad
+            .iter()
+            .all(|interval| allowed.contains(&(interval.semitones % 12)))
+        {
+            forms.push(names(
+                symbol,
+                &[third, seventh, triad[0], triad[1], triad[2]],
+            )?);
+        }
+    }
+
+    Ok(forms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(low: &str, high: &str) -> VoicingRange {
+        VoicingRange {
+            low: Note::from_str(low).unwrap(),
+            high: Note::from_str(high).unwrap(),
+        }
+    }
+
+    fn voice(symbol: &str, kind: VoicingType, range: &VoicingRange) -> Vec<String> {
+        voicings(&ChordSymbol::from_str(symbol).unwrap(), kind, range)
+            .unwrap()
+            .iter()
+            .map(|voicing| {
+                voicing
+                    .notes
+                    .iter()
+                    .map(|note| note.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shells() {
+        let low = range("C3", "C5");
+        assert_eq!(
+            voice("Cmaj7", VoicingType::Shell, &low),
+            ["C3 E3 B3", "C3 B3 E4"]
+        );
+        assert_eq!(
+            voice("F7", VoicingType::Shell, &low),
+            ["F3 A3 Eb4", "F3 Eb4 A4"]
+        );
+        assert_eq!(
+            voicings(
+                &ChordSymbol::from_str("C").unwrap(),
+                VoicingType::Shell,
+                &low
+            ),
+            Err(VoicingError::NotASeventhChord)
+        );
+    }
+
+    #[test]
+    fn test_drop_voicings() {
+        let wide = range("C3", "C6");
+        assert_eq!(
+            voice("Cmaj7", VoicingType::Drop2, &wide),
+            ["G3 C4 E4 B4", "B3 E4 G4 C5", "C3 G3 B3 E4", "E3 B3 C4 G4"]
+        );
+        assert_eq!(voice("Cmaj7", VoicingType::Drop3, &wide)[0], "E3 C4 G4 B4");
+        assert_eq!(voice("Dm7(9)", VoicingType::Drop2, &wide)[0], "A3 E4 F4 C5");
+    }
+
+    #[test]
+    fn test_rootless_voicings() {
+        let middle = range("C3", "C5");
+        assert_eq!(
+            voice("G7", VoicingType::RootlessA, &middle),
+            ["B3 E4 F4 A4"]
+        );
+        assert_eq!(
+            voice("G7", VoicingType::RootlessB, &middle),
+            ["F3 A3 B3 E4"]
+        );
+        assert_eq!(
+            voice("Dm7", VoicingType::RootlessA, &middle),
+            ["F3 A3 C4 E4"]
+        );
+        assert_eq!(
+            voice("Cmaj7", VoicingType::RootlessB, &middle),
+            ["B3 D4 E4 G4"]
+        );
+        assert_eq!(
+            voice("G7(b9,b13)", VoicingType::RootlessA, &middle),
+            ["B3 Eb4 F4 Ab4"]
+        );
+
+        // Locrian's b9 is an avoid note, so the root takes the 9th's place.
+        assert_eq!(
+            voice("Bm7b5", VoicingType::RootlessA, &middle),
+            ["D3 F3 A3 B3"]
+        );
+    }
+
+    #[test]
+    fn test_so_what() {
+        let wide = range("C3", "C6");
+        assert_eq!(
+            voice("Dm7", VoicingType::SoWhat, &wide)[0],
+            "D3 G3 C4 F4 A4"
+        );
+        assert!(voice("Cmaj7", VoicingType::SoWhat, &wide).contains(&"E3 A3 D4 G4 B4".to_string()));
+
+        // Every note has to be in the chord-scale and not an avoid note.
+        for voicing in voice("Cmaj7", VoicingType::SoWhat, &wide) {
+            assert!(!voicing.contains('F'), "{voicing}");
+        }
+    }
+
+    #[test]
+    fn test_upper_structures() {
+        let wide = range("C3", "C6");
+        assert_eq!(
+            voice("C7(9,#11,13)", VoicingType::UpperStructure, &wide),
+            ["E3 Bb3 D4 F#4 A4"]
+        );
+        assert_eq!(
+            voice("C7alt", VoicingType::UpperStructure, &wide),
+            ["E3 Bb3 Gb4 Bb4 Db5", "E3 Bb3 Ab4 C5 Eb5"]
+        );
+        assert_eq!(
+            voicings(
+                &ChordSymbol::from_str("Cm7").unwrap(),
+                VoicingType::UpperStructure,
+                &wide
+            ),
+            Err(VoicingError::NotDominant)
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let piano = VoicingRange::piano();
+        for voicing in voicings(
+            &ChordSymbol::from_str("Ebmaj7").unwrap(),
+            VoicingType::Drop2,
+            &piano,
+        )
+        .unwrap()
+        {
+            assert!(voicing.notes.iter().all(|note| piano.contains(note)));
+            assert_eq!(voicing.notes.len(), 4);
+        }
+
+        // Nothing spanning more than a 3rd fits in a 3rd.
+        assert!(voice("Cmaj7", VoicingType::Shell, &range("C4", "E4")).is_empty());
+    }
+}